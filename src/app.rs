@@ -1,19 +1,48 @@
-use std::time::Duration;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use crossterm::event::{self, poll, Event, KeyCode};
+use crossterm::event::{
+    self, poll, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use image::DynamicImage;
 use itertools::{enumerate, Itertools};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
     DefaultTerminal, Frame,
 };
+use ratatui_image::picker::{Picker, ProtocolType};
+use ratatui_image::protocol::StatefulProtocol;
+use ratatui_image::StatefulImage;
+
+use serde::{Deserialize, Serialize};
+
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::config::{LatestAddedSort, Rewatch, SearchMatcher, UnwatchedSort};
+use crate::jellyfin::{
+    episodes_before, format_ticks, Jellyfin, LibraryStats, MediaItem, MediaSource, PlaybackControl,
+    PlayerFailed, PlayerNotFound, RefreshSummary,
+};
+use crate::state::State;
+use crate::theme::Theme;
+use crate::websocket::ServerEvent;
 
-use crate::jellyfin::{Jellyfin, MediaItem};
+// ? a detached player runs on a copy of the client, which is handed back once it is done
+type Playback = JoinHandle<(Jellyfin, Result<Option<MediaItem>>)>;
+type LibraryUpdate = JoinHandle<Result<Vec<MediaItem>>>;
+type SessionsPoll = JoinHandle<Result<HashMap<String, Vec<String>>>>;
 
 pub struct App {
     jellyfin: Jellyfin,
@@ -26,16 +55,64 @@ pub struct App {
     movies: Vec<MediaItem>,
     series: Vec<MediaItem>,
     episodes: Vec<MediaItem>,
+    artists: Vec<MediaItem>,
+    next_up: Vec<MediaItem>,
+    recommended: Vec<MediaItem>,
+    unwatched: Vec<MediaItem>,
+    recommended_for: Option<String>,
     filtered: Vec<MediaItem>,
+    // ? items marked with space, which the batch actions apply to instead of the highlighted one
+    selected_ids: HashSet<String>,
+    undo_stack: Vec<Toggle>,
+    latest_library: Option<String>,
+    last_click: Option<(Instant, SelectionState, usize)>,
+    details: HashMap<String, MediaItem>,
+    details_fetch: Option<(MediaItem, JoinHandle<Result<MediaItem>>)>,
+    // ? None when the terminal has no graphics protocol
+    picker: Option<Picker>,
+    stills: HashMap<String, Option<StatefulProtocol>>,
+    still_fetch: Option<(String, JoinHandle<Option<DynamicImage>>)>,
+    season_names: HashMap<String, HashMap<i64, String>>,
+    // ? what is left to watch of each selected series, dropped whenever watched states change
+    remaining: HashMap<String, String>,
+    // ? kept open, on x11 the copied text is gone once the clipboard is dropped
+    clipboard: Option<arboard::Clipboard>,
+    rng: StdRng,
+    detached: Option<(String, Vec<MediaItem>, Playback)>,
+    playing_elsewhere: HashMap<String, Vec<String>>,
+    last_sessions_poll: Option<Instant>,
+    sessions_poll: Option<SessionsPoll>,
+    info_scroll: Option<(String, u16)>,
+    search_history: Vec<String>,
+    history_index: Option<usize>,
+    theme: Theme,
+    events: UnboundedReceiver<ServerEvent>,
+    library_update: Option<(Vec<String>, LibraryUpdate)>,
     config: Config,
 }
 
 struct Config {
     include_episodes: bool,
+    next_up_by_last_watched: bool,
+    episodes_newest_first: bool,
 }
 
-#[derive(PartialEq)]
-enum Page {
+const SEARCH_HISTORY_LENGTH: usize = 20;
+
+const HOME_PAGES: [Page; 9] = [
+    Page::ContinueWatching,
+    Page::NextUp,
+    Page::LatestAdded,
+    Page::Recommended,
+    Page::Unwatched,
+    Page::AllMovies,
+    Page::AllSeries,
+    Page::Music,
+    Page::Playlists,
+];
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub(crate) enum Page {
     All,
     Movies,
     Series,
@@ -43,11 +120,15 @@ enum Page {
     ContinueWatching,
     NextUp,
     LatestAdded,
+    Recommended,
+    Unwatched,
     AllMovies,
     AllSeries,
+    Music,
+    Playlists,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 enum SelectionState {
     Main,
     Episode,
@@ -55,8 +136,147 @@ enum SelectionState {
 
 enum Action {
     None,
-    NowPlaying(MediaItem),
+    // ? a start position overrides resuming
+    NowPlaying(Box<MediaItem>, Option<MediaSource>, Option<i64>),
+    ChoosingSource(Box<MediaItem>, Vec<MediaSource>, usize, Option<i64>),
+    ChoosingPlaylist(Vec<MediaItem>, usize),
+    // ? index 0 is all libraries, the rest are offset by one
+    ChoosingLibrary(usize),
+    AddingToPlaylist(Vec<MediaItem>, Box<MediaItem>),
+    ConfirmingDelete(Box<MediaItem>),
+    Deleting(Box<MediaItem>),
+    // ? marking as unplayed puts back the watched state the items had before
+    MarkingPlayed(Batch, bool),
+    Favoriting(Batch, bool),
+    ConfirmingShuffle(Box<MediaItem>),
+    ConfirmingRewatch(Box<MediaItem>),
+    EnteringStart(Box<MediaItem>, String),
+    PlayingQueue(Vec<MediaItem>),
+    ConfirmingRefresh,
+    RefreshingItem(Box<MediaItem>),
+    LoadingEpisodes(Box<MediaItem>),
+    CopyingUrl(Box<MediaItem>),
+    Copied(Box<MediaItem>),
     RefreshingCache,
+    RefreshingHome,
+    RefreshComplete(RefreshSummary),
+    ShowingStats(LibraryStats),
+    ShowingMenu(Box<MediaItem>, usize),
+    ReadingOverview(Box<MediaItem>, u16),
+    Error(String),
+}
+
+struct MenuEntry {
+    label: String,
+    hint: &'static str,
+    command: MenuCommand,
+}
+
+enum MenuCommand {
+    Key(KeyCode, KeyModifiers),
+    MarkPlayed,
+}
+
+// ? a watched or favorite change with the items it actually changed, so it can be reverted
+enum Toggle {
+    Played(Vec<MediaItem>),
+    Favorite(Vec<MediaItem>, bool),
+}
+
+// ? changed one item per pass, so the popup is redrawn with the progress in between. only the
+// ? items changed successfully can be undone, and undoing isn't undoable itself
+struct Batch {
+    pending: Vec<MediaItem>,
+    done: Vec<MediaItem>,
+    undoing: bool,
+}
+
+impl Batch {
+    fn new(items: Vec<MediaItem>) -> Self {
+        Batch {
+            pending: items,
+            done: Vec::new(),
+            undoing: false,
+        }
+    }
+
+    fn undo(items: Vec<MediaItem>) -> Self {
+        Batch {
+            undoing: true,
+            ..Batch::new(items)
+        }
+    }
+
+    fn advance(&self) -> Option<Batch> {
+        (self.pending.len() > 1).then(|| Batch {
+            pending: self.pending[1..].to_vec(),
+            done: [self.done.as_slice(), &self.pending[..1]].concat(),
+            undoing: self.undoing,
+        })
+    }
+
+    fn finish(&self, succeeded: bool) -> Option<Vec<MediaItem>> {
+        let mut done = self.done.clone();
+        if succeeded {
+            done.push(self.pending[0].clone());
+        }
+
+        (!self.undoing && !done.is_empty()).then_some(done)
+    }
+
+    fn progress(&self) -> String {
+        format!(
+            "{} of {}",
+            self.done.len() + 1,
+            self.done.len() + self.pending.len()
+        )
+    }
+}
+
+const UNDO_LIMIT: usize = 10;
+
+fn remember(undo_stack: &mut Vec<Toggle>, toggle: Toggle) {
+    if undo_stack.len() == UNDO_LIMIT {
+        undo_stack.remove(0);
+    }
+    undo_stack.push(toggle);
+}
+
+// ? keys aren't read while a player is monitored, so the stop key is watched for alongside it
+async fn until_stopped<T>(
+    playback: impl Future<Output = Result<T>>,
+    control: PlaybackControl,
+) -> Result<T> {
+    tokio::pin!(playback);
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+
+    loop {
+        tokio::select! {
+            result = &mut playback => return result,
+            _ = interval.tick() => {
+                while poll(Duration::ZERO)? {
+                    if let Event::Key(key) = event::read()? {
+                        // ? a player that can't be stopped is still waited for
+                        if key.code == KeyCode::Char('x')
+                            && key.modifiers.contains(KeyModifiers::CONTROL)
+                        {
+                            let _ = control.stop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl MenuEntry {
+    fn key(label: &str, hint: &'static str, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        MenuEntry {
+            label: label.to_string(),
+            hint,
+            command: MenuCommand::Key(code, modifiers),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -64,6 +284,7 @@ struct Selection {
     index: usize,
     scroll_position: usize,
     visible_height: usize,
+    area: Rect,
     series: Option<MediaItem>,
     episodes: Option<Vec<MediaItem>>,
 }
@@ -74,14 +295,448 @@ impl Selection {
             index: 0,
             scroll_position: 0,
             visible_height: 0,
+            area: Rect::default(),
             series: None,
             episodes: None,
         }
     }
 }
 
+// ? substring and prefix matches score like a decent fuzzy match, earlier and in shorter names is better
+fn match_score(
+    matcher: SearchMatcher,
+    fuzzy: &SkimMatcherV2,
+    name: &str,
+    query: &str,
+) -> Option<i64> {
+    let name_lower = name.to_lowercase();
+
+    match matcher {
+        SearchMatcher::Fuzzy => fuzzy.fuzzy_match(name, query),
+        SearchMatcher::Substring => name_lower
+            .find(query)
+            .map(|position| 100 - position as i64 - name.len() as i64),
+        SearchMatcher::Prefix => name_lower
+            .split_whitespace()
+            .position(|word| word.starts_with(query))
+            .map(|word| 100 - 10 * word as i64 - name.len() as i64),
+    }
+}
+
+// ? weight is added to the score of every matching item. the minimum only applies to the fuzzy score itself,
+// ? substring and prefix scores are on a scale of their own and every match of theirs is a real one
+fn search_filter(
+    pool: &[MediaItem],
+    query: &str,
+    matcher: SearchMatcher,
+    min_score: Option<i64>,
+    weight: impl Fn(&MediaItem) -> i64,
+) -> Vec<MediaItem> {
+    if query.is_empty() {
+        return pool.to_vec();
+    }
+
+    let fuzzy = SkimMatcherV2::default();
+    let query = query.to_lowercase();
+
+    pool.iter()
+        .filter_map(|item| {
+            match_score(matcher, &fuzzy, &item.name, &query)
+                .filter(|score| {
+                    matcher != SearchMatcher::Fuzzy || min_score.is_none_or(|min| *score >= min)
+                })
+                .map(|score| (item, score + weight(item)))
+        })
+        .sorted_by(|(_, a), (_, b)| b.cmp(a))
+        .map(|(item, _)| item.clone())
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum FilterField {
+    Year,
+    Rating,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Comparison {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct QueryFilter {
+    field: FilterField,
+    comparison: Comparison,
+    value: f32,
+}
+
+impl QueryFilter {
+    // ? items without the field never match, e.g. unrated items are left out by any rating filter
+    fn matches(&self, item: &MediaItem) -> bool {
+        let value = match self.field {
+            FilterField::Year => item.year.map(|year| year as f32),
+            FilterField::Rating => item.imdb_rating,
+        };
+
+        let Some(value) = value else {
+            return false;
+        };
+
+        match self.comparison {
+            Comparison::Equal => value == self.value,
+            Comparison::Greater => value > self.value,
+            Comparison::GreaterOrEqual => value >= self.value,
+            Comparison::Less => value < self.value,
+            Comparison::LessOrEqual => value <= self.value,
+        }
+    }
+}
+
+// ? year:1999, rating:>7 or year:1980-1989, anything that doesn't parse is searched as text
+fn parse_filter(token: &str) -> Option<Vec<QueryFilter>> {
+    let (field, value) = token.split_once(':')?;
+
+    let field = match field.to_lowercase().as_str() {
+        "year" => FilterField::Year,
+        "rating" => FilterField::Rating,
+        _ => return None,
+    };
+
+    let filter = |comparison, value: &str| {
+        value.parse().ok().map(|value| QueryFilter {
+            field,
+            comparison,
+            value,
+        })
+    };
+
+    let filters = if let Some((min, max)) = value.split_once('-').filter(|(min, _)| !min.is_empty())
+    {
+        vec![
+            filter(Comparison::GreaterOrEqual, min)?,
+            filter(Comparison::LessOrEqual, max)?,
+        ]
+    } else if let Some(value) = value.strip_prefix(">=") {
+        vec![filter(Comparison::GreaterOrEqual, value)?]
+    } else if let Some(value) = value.strip_prefix("<=") {
+        vec![filter(Comparison::LessOrEqual, value)?]
+    } else if let Some(value) = value.strip_prefix('>') {
+        vec![filter(Comparison::Greater, value)?]
+    } else if let Some(value) = value.strip_prefix('<') {
+        vec![filter(Comparison::Less, value)?]
+    } else {
+        vec![filter(Comparison::Equal, value.trim_start_matches('='))?]
+    };
+
+    Some(filters)
+}
+
+fn parse_query(query: &str) -> (String, Vec<QueryFilter>) {
+    let mut words = Vec::new();
+    let mut filters = Vec::new();
+
+    for token in query.split_whitespace() {
+        match parse_filter(token) {
+            Some(parsed) => filters.extend(parsed),
+            None => words.push(token),
+        }
+    }
+
+    (words.join(" "), filters)
+}
+
+// ? the series watched most recently comes first, series that were never played keep the server's order at the end
+fn sort_by_last_watched(
+    episodes: &[MediaItem],
+    items: &HashMap<String, MediaItem>,
+) -> Vec<MediaItem> {
+    episodes
+        .iter()
+        .sorted_by_cached_key(|episode| {
+            std::cmp::Reverse(
+                episode
+                    .series_id
+                    .as_ref()
+                    .and_then(|id| items.get(id))
+                    .and_then(|series| series.user_data.as_ref())
+                    .and_then(|data| data.last_played_date.clone()),
+            )
+        })
+        .cloned()
+        .collect()
+}
+
+// ? movies that were never watched and the next episode of each series that was started, which is
+// ? one with any episode watched. worked out from the cache, next up from the server is cut off
+fn unwatched(items: &HashMap<String, MediaItem>, sort: UnwatchedSort) -> Vec<MediaItem> {
+    let played = |item: &MediaItem| item.user_data.as_ref().is_some_and(|data| data.played);
+
+    let movies = items
+        .values()
+        .filter(|item| item.type_ == "Movie" && !played(item))
+        .cloned();
+
+    let next_episodes = items
+        .values()
+        .filter(|item| item.type_ == "Episode" && item.series_id.is_some())
+        .into_group_map_by(|episode| episode.series_id.clone())
+        .into_values()
+        .filter(|episodes| episodes.iter().any(|episode| played(episode)))
+        .filter_map(|episodes| {
+            let episodes: Vec<_> = episodes
+                .into_iter()
+                .sorted_by_key(|episode| {
+                    (
+                        episode.parent_index_number.unwrap_or(0),
+                        episode.index_number.unwrap_or(0),
+                    )
+                })
+                .cloned()
+                .collect();
+
+            // ? a series watched to the end has no next episode, rather than starting over
+            next_unwatched(&episodes).filter(|episode| !played(episode))
+        });
+
+    movies
+        .chain(next_episodes)
+        .sorted_by(|a, b| match sort {
+            UnwatchedSort::DateCreated => b.date_created.cmp(&a.date_created),
+            UnwatchedSort::Rating => b
+                .imdb_rating
+                .unwrap_or(0.0)
+                .total_cmp(&a.imdb_rating.unwrap_or(0.0)),
+        })
+        .collect()
+}
+
+// ? based on the movie or series watched most recently, ranked by how many genres they share
+// ? with it, without anything that was watched already
+fn recommendations(items: &HashMap<String, MediaItem>) -> Option<(MediaItem, Vec<MediaItem>)> {
+    let titles = || {
+        items
+            .values()
+            .filter(|item| matches!(item.type_.as_str(), "Movie" | "Series"))
+    };
+
+    let watched = titles()
+        .filter(|item| !item.genres.is_empty())
+        .filter_map(|item| {
+            let last_played = item.user_data.as_ref()?.last_played_date.as_ref()?;
+            Some((last_played, item))
+        })
+        .max_by_key(|(last_played, _)| *last_played)?
+        .1;
+
+    let recommended = titles()
+        .filter(|item| item.id != watched.id)
+        .filter(|item| !item.user_data.as_ref().is_some_and(|data| data.played))
+        .filter_map(|item| {
+            let shared = item
+                .genres
+                .iter()
+                .filter(|genre| watched.genres.contains(genre))
+                .count();
+            (shared > 0).then_some((shared, item))
+        })
+        .sorted_by(|(a_shared, a), (b_shared, b)| {
+            b_shared
+                .cmp(a_shared)
+                .then(
+                    b.imdb_rating
+                        .unwrap_or(0.0)
+                        .total_cmp(&a.imdb_rating.unwrap_or(0.0)),
+                )
+                .then(a.name.cmp(&b.name))
+        })
+        .take(12)
+        .map(|(_, item)| item.clone())
+        .collect();
+
+    Some((watched.clone(), recommended))
+}
+
+// ? starts over from the first episode once everything is watched
+fn next_unwatched(episodes: &[MediaItem]) -> Option<MediaItem> {
+    let playable: Vec<_> = episodes.iter().filter(|ep| ep.is_playable()).collect();
+
+    playable
+        .iter()
+        .find(|ep| !ep.user_data.as_ref().is_some_and(|data| data.played))
+        .or(playable.first())
+        .map(|ep| (*ep).clone())
+}
+
+fn shuffle_pick(episodes: &[MediaItem], rng: &mut impl Rng) -> Option<MediaItem> {
+    let unwatched: Vec<_> = episodes
+        .iter()
+        .filter(|episode| !episode.user_data.as_ref().is_some_and(|data| data.played))
+        .collect();
+
+    if unwatched.is_empty() {
+        episodes.choose(rng).cloned()
+    } else {
+        unwatched.choose(rng).copied().cloned()
+    }
+}
+
+// ? a plain number or one ending in % is a percentage of the runtime, anything with : a timestamp
+fn parse_start(input: &str, runtime_ticks: Option<i64>) -> Option<i64> {
+    let input = input.trim();
+
+    if input.contains(':') {
+        let mut seconds = 0;
+        for part in input.split(':') {
+            seconds = seconds * 60 + part.parse::<u32>().ok()? as i64;
+        }
+
+        return Some(seconds * 10_000_000);
+    }
+
+    let percentage = input.trim_end_matches('%').trim().parse::<f64>().ok()?;
+    if !(0.0..=100.0).contains(&percentage) {
+        return None;
+    }
+
+    Some((runtime_ticks? as f64 * percentage / 100.0) as i64)
+}
+
+// ? unknown and repeated names are skipped, with nothing valid left every home page is shown
+fn enabled_pages(names: &[String]) -> Vec<Page> {
+    let mut pages = Vec::new();
+
+    for page in names.iter().filter_map(|name| Page::from_config(name)) {
+        if !pages.contains(&page) {
+            pages.push(page);
+        }
+    }
+
+    if pages.is_empty() {
+        HOME_PAGES.to_vec()
+    } else {
+        pages
+    }
+}
+
+// ? counts display columns, so wide characters (CJK, most emoji) take up two
+fn truncate_to_width(text: &str, width: usize) -> String {
+    let mut used = 0;
+
+    for (index, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+
+        if used > width {
+            let mut truncated = text[..index].to_string();
+
+            // make room for the ellipsis
+            while truncated.width() + 1 > width {
+                if truncated.pop().is_none() {
+                    break;
+                }
+            }
+
+            truncated.push('…');
+            return truncated;
+        }
+    }
+
+    text.to_string()
+}
+
+fn remaining_summary(episodes: usize, ticks: i64) -> String {
+    match episodes {
+        0 => "All episodes watched".to_string(),
+        1 => format!("1 episode left · ~{}", format_ticks(ticks)),
+        _ => format!("{} episodes left · ~{}", episodes, format_ticks(ticks)),
+    }
+}
+
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+
+    digits
+        .chars()
+        .enumerate()
+        .flat_map(|(index, digit)| {
+            let separator = (index > 0 && (digits.len() - index).is_multiple_of(3)).then_some(',');
+            separator.into_iter().chain([digit])
+        })
+        .collect()
+}
+
+fn describe_items(items: &[MediaItem]) -> String {
+    match items {
+        [item] => item.name.clone(),
+        _ => format!("{} items", items.len()),
+    }
+}
+
+impl Page {
+    fn from_config(name: &str) -> Option<Self> {
+        match name {
+            "continue_watching" => Some(Page::ContinueWatching),
+            "next_up" => Some(Page::NextUp),
+            "latest_added" => Some(Page::LatestAdded),
+            "recommended" => Some(Page::Recommended),
+            "unwatched" => Some(Page::Unwatched),
+            "movies" => Some(Page::AllMovies),
+            "series" => Some(Page::AllSeries),
+            "music" => Some(Page::Music),
+            "playlists" => Some(Page::Playlists),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Page::All => "All",
+            Page::Movies | Page::AllMovies => "Movies",
+            Page::Series | Page::AllSeries => "Series",
+            Page::Episodes => "Episodes",
+            Page::ContinueWatching => "Continue Watching",
+            Page::NextUp => "Next Up",
+            Page::LatestAdded => "Latest Added",
+            Page::Recommended => "Recommended",
+            Page::Unwatched => "Unwatched",
+            Page::Music => "Music",
+            Page::Playlists => "Playlists",
+        }
+    }
+
+    fn is_home(&self) -> bool {
+        matches!(
+            self,
+            Page::ContinueWatching
+                | Page::NextUp
+                | Page::LatestAdded
+                | Page::Recommended
+                | Page::Unwatched
+                | Page::AllMovies
+                | Page::AllSeries
+                | Page::Music
+                | Page::Playlists
+        )
+    }
+}
+
 impl App {
     pub fn new(jellyfin: Jellyfin) -> Result<Self> {
+        let events = jellyfin.connect_websocket();
+        let state = State::load(&jellyfin.state_path());
+        let include_episodes = state
+            .include_episodes
+            .unwrap_or(jellyfin.config().include_episodes);
+        let next_up_by_last_watched = state
+            .next_up_by_last_watched
+            .unwrap_or(jellyfin.config().next_up_by_last_watched);
+        let episodes_newest_first = state
+            .episodes_newest_first
+            .unwrap_or(jellyfin.config().episodes_newest_first);
+
         let mut app = Self {
             jellyfin,
             current_action: Action::None,
@@ -93,13 +748,58 @@ impl App {
             movies: Vec::new(),
             series: Vec::new(),
             episodes: Vec::new(),
+            artists: Vec::new(),
+            next_up: Vec::new(),
+            recommended: Vec::new(),
+            unwatched: Vec::new(),
+            recommended_for: None,
             filtered: Vec::new(),
+            last_click: None,
+            details: HashMap::new(),
+            details_fetch: None,
+            picker: None,
+            stills: HashMap::new(),
+            still_fetch: None,
+            season_names: HashMap::new(),
+            remaining: HashMap::new(),
+            clipboard: None,
+            selected_ids: HashSet::new(),
+            undo_stack: Vec::new(),
+            latest_library: None,
+            rng: StdRng::from_entropy(),
+            detached: None,
+            playing_elsewhere: HashMap::new(),
+            last_sessions_poll: None,
+            sessions_poll: None,
+            info_scroll: None,
+            search_history: Vec::new(),
+            history_index: None,
+            theme: Theme::default(),
+            events,
+            library_update: None,
             config: Config {
-                include_episodes: false,
+                include_episodes,
+                next_up_by_last_watched,
+                episodes_newest_first,
             },
         };
 
-        app.movies = app
+        app.theme = Theme::from_config(
+            &app.jellyfin.config().theme,
+            app.jellyfin.config().no_color(),
+        )?;
+
+        app.load_items();
+
+        app.restore_state(state);
+
+        Ok(app)
+    }
+
+    fn load_items(&mut self) {
+        self.remaining.clear();
+
+        self.movies = self
             .jellyfin
             .items
             .values()
@@ -108,7 +808,7 @@ impl App {
             .sorted_by(|a, b| a.name.cmp(&b.name))
             .collect();
 
-        app.series = app
+        self.series = self
             .jellyfin
             .items
             .values()
@@ -117,7 +817,7 @@ impl App {
             .sorted_by(|a, b| a.name.cmp(&b.name))
             .collect();
 
-        app.episodes = app
+        self.episodes = self
             .jellyfin
             .items
             .values()
@@ -126,7 +826,103 @@ impl App {
             .sorted_by(|a, b| a.name.cmp(&b.name))
             .collect();
 
-        Ok(app)
+        self.artists = self
+            .jellyfin
+            .items
+            .values()
+            .filter(|item| item.type_ == "MusicArtist")
+            .cloned()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect();
+
+        self.next_up = if self.config.next_up_by_last_watched {
+            sort_by_last_watched(&self.jellyfin.next_up, &self.jellyfin.items)
+        } else {
+            self.jellyfin.next_up.clone()
+        };
+
+        self.unwatched = unwatched(&self.jellyfin.items, self.jellyfin.config().unwatched_sort);
+
+        (self.recommended_for, self.recommended) = match recommendations(&self.jellyfin.items) {
+            Some((watched, recommended)) => (Some(watched.name), recommended),
+            None => (None, Vec::new()),
+        };
+
+        // ? an episode that is half watched already shows in continue watching
+        if self.home_pages().contains(&Page::ContinueWatching) {
+            let resuming: HashSet<&MediaItem> = self.jellyfin.continue_watching.iter().collect();
+            self.next_up.retain(|item| !resuming.contains(item));
+        }
+    }
+
+    fn restore_state(&mut self, state: State) {
+        self.search_history = state.search_history;
+        self.latest_library = state.latest_library;
+
+        let home_pages = self.home_pages();
+        self.page = home_pages[0];
+
+        // ? a configured startup page wins over the one open when last quitting
+        match self.jellyfin.config().startup_page.as_deref() {
+            Some(name) => match Page::from_config(name) {
+                Some(page) if home_pages.contains(&page) => self.page = page,
+                _ => {
+                    self.current_action = Action::Error(format!(
+                        "Unknown or disabled startup_page \"{}\", using {}",
+                        name,
+                        self.page.title()
+                    ))
+                }
+            },
+            // ? search pages depend on a query, so only home pages are restored
+            None => {
+                if let Some(page) = state.page.filter(|page| home_pages.contains(page)) {
+                    self.page = page;
+                }
+            }
+        }
+
+        let Some(selected_id) = state.selected_id else {
+            return;
+        };
+
+        // the remembered item may have been removed since, in which case stay at the top
+        if let Some(index) = self
+            .selection_options(Some(&SelectionState::Main))
+            .iter()
+            .position(|item| item.id == selected_id)
+        {
+            self.main_selection.index = index;
+        }
+    }
+
+    fn save_state(&self) -> Result<()> {
+        let mut state = if self.page.is_home() {
+            State {
+                page: Some(self.page),
+                selected_id: self
+                    .selection_options(Some(&SelectionState::Main))
+                    .get(self.main_selection.index)
+                    .map(|item| item.id.clone()),
+                ..Default::default()
+            }
+        } else {
+            State::default()
+        };
+
+        state.search_history = self.search_history.clone();
+        state.latest_library = self.latest_library.clone();
+
+        // ? only what differs from config.toml, so changing a default there still takes effect
+        let config = self.jellyfin.config();
+        state.include_episodes =
+            Some(self.config.include_episodes).filter(|toggle| *toggle != config.include_episodes);
+        state.next_up_by_last_watched = Some(self.config.next_up_by_last_watched)
+            .filter(|toggle| *toggle != config.next_up_by_last_watched);
+        state.episodes_newest_first = Some(self.config.episodes_newest_first)
+            .filter(|toggle| *toggle != config.episodes_newest_first);
+
+        state.save(&self.jellyfin.state_path())
     }
 
     pub async fn run(
@@ -134,16 +930,30 @@ impl App {
         terminal: &mut DefaultTerminal,
         render_outer: impl Fn(&mut Frame) -> Rect,
     ) -> Result<()> {
+        // ? queried once the terminal is set up, half blocks are too coarse for a still to help
+        self.picker = Picker::from_query_stdio()
+            .ok()
+            .filter(|picker| picker.protocol_type() != ProtocolType::Halfblocks);
+
         loop {
+            self.check_detached_playback().await;
+            self.check_library_update().await;
+            self.poll_sessions().await;
+            self.fetch_selected_details().await;
+            self.fetch_selected_still().await;
+            self.fetch_selected_season_names().await;
+            self.count_selected_remaining();
             self.draw(terminal, &render_outer)?;
             if self.handle_action().await? {
                 continue;
             }
+            self.handle_server_events();
             if !self.handle_input()? {
                 break;
             }
         }
 
+        self.save_state()?;
         self.jellyfin.cleanup()?;
 
         Ok(())
@@ -170,14 +980,29 @@ impl App {
         }
     }
 
+    fn visible_height(&self, state: Option<&SelectionState>) -> usize {
+        match state.unwrap_or(&self.selection_state) {
+            SelectionState::Main => self.main_selection.visible_height,
+            SelectionState::Episode => self.episode_selection.visible_height,
+        }
+    }
+
     fn selection_options(&self, state: Option<&SelectionState>) -> &Vec<MediaItem> {
         match state.unwrap_or(&self.selection_state) {
             SelectionState::Main => match self.page {
                 Page::ContinueWatching => &self.jellyfin.continue_watching,
-                Page::NextUp => &self.jellyfin.next_up,
-                Page::LatestAdded => &self.jellyfin.latest_added,
+                Page::NextUp => &self.next_up,
+                Page::LatestAdded => self
+                    .latest_library
+                    .as_deref()
+                    .and_then(|id| self.jellyfin.latest_in_library(id))
+                    .unwrap_or(&self.jellyfin.latest_added),
                 Page::AllMovies => &self.movies,
                 Page::AllSeries => &self.series,
+                Page::Music => &self.artists,
+                Page::Playlists => &self.jellyfin.playlists,
+                Page::Recommended => &self.recommended,
+                Page::Unwatched => &self.unwatched,
                 _ => &self.filtered,
             },
             SelectionState::Episode => {
@@ -194,72 +1019,413 @@ impl App {
         self.selection_options(None).get(self.index(None)).cloned()
     }
 
-    fn search(&mut self) {
-        let mut all;
-        let pool = match self.page {
-            Page::All => {
-                all = self.movies.clone();
-                all.extend(self.series.clone());
-                if self.config.include_episodes {
-                    all.extend(self.episodes.clone());
-                }
-                &all
-            }
-            Page::Movies => &self.movies,
-            Page::Series => &self.series,
-            Page::Episodes => &self.episodes,
-            _ => return,
-        };
-
-        if self.query.is_empty() {
-            self.filtered = pool.to_vec();
-            return;
+    fn batch_items(&self) -> Vec<MediaItem> {
+        if self.selected_ids.is_empty() {
+            return self.selected_item().into_iter().collect();
         }
 
-        let matcher = SkimMatcherV2::default();
-
-        self.filtered = pool
+        self.selected_ids
             .iter()
-            .map(|item| {
-                (
-                    item,
-                    matcher.fuzzy_match(&item.name, &self.query.to_lowercase()),
-                )
+            .filter_map(|id| {
+                self.jellyfin.items.get(id).or_else(|| {
+                    self.selection_options(None)
+                        .iter()
+                        .find(|item| item.id == *id)
+                })
             })
-            .filter(|(_, score)| score.is_some())
-            .sorted_by(|(_, a), (_, b)| b.cmp(a))
-            .map(|(item, _)| item.clone())
-            .collect();
+            .cloned()
+            .sorted_by(|a, b| a.name.cmp(&b.name))
+            .collect()
     }
 
-    fn draw(
-        &mut self,
-        terminal: &mut DefaultTerminal,
-        render_outer: impl Fn(&mut Frame) -> Rect,
-    ) -> Result<()> {
-        terminal.draw(|frame| {
-            let inner_area = render_outer(frame);
-            let main_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-                .split(inner_area);
+    // ? moves on to the next item, so a run of items is marked by holding space
+    fn toggle_marked(&mut self) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
 
-            self.draw_media_panel(frame, main_chunks[0], self.selected_item());
+        if !self.selected_ids.remove(&item.id) {
+            self.selected_ids.insert(item.id);
+        }
 
-            let right_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .split(main_chunks[1]);
+        if self.index(None) + 1 < self.selection_options(None).len() {
+            self.set_index(self.index(None) + 1);
+        }
+    }
 
-            let right_top_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Length(3), Constraint::Min(0)])
-                .split(right_chunks[0]);
+    fn selected_item_in(&self, state: &SelectionState) -> Option<MediaItem> {
+        self.selection_options(Some(state))
+            .get(self.index(Some(state)))
+            .cloned()
+    }
 
-            self.draw_search_bar(frame, right_top_chunks[0]);
+    async fn poll_sessions(&mut self) {
+        if let Some(poll) = self.sessions_poll.take_if(|poll| poll.is_finished()) {
+            // ? purely informational, so a failed poll just keeps the last known state
+            if let Ok(Ok(playing)) = poll.await {
+                self.playing_elsewhere = playing;
+            }
+        }
+
+        if self.sessions_poll.is_some()
+            || self
+                .last_sessions_poll
+                .is_some_and(|last| last.elapsed() < Duration::from_secs(30))
+        {
+            return;
+        }
+
+        self.last_sessions_poll = Some(Instant::now());
+        self.sessions_poll = Some(tokio::spawn(self.jellyfin.fetch_playing_elsewhere()));
+    }
+
+    // ? episodes are cached without their overview, so it is fetched once they are selected
+    async fn fetch_selected_details(&mut self) {
+        if let Some((item, fetch)) = self.details_fetch.take_if(|(_, fetch)| fetch.is_finished()) {
+            // ? on failure the slim item is kept, so it isn't requested again on every redraw
+            let details = match fetch.await {
+                Ok(Ok(details)) => details,
+                _ => item,
+            };
+            self.details.insert(details.id.clone(), details);
+        }
+
+        if self.details_fetch.is_some() {
+            return;
+        }
+
+        let Some(item) = self
+            .selected_item()
+            .filter(|item| item.type_ == "Episode" && item.overview.is_none())
+            .filter(|item| !self.details.contains_key(&item.id))
+        else {
+            return;
+        };
+
+        let fetch = tokio::spawn(self.jellyfin.fetch_details_in_background(&item.id));
+        self.details_fetch = Some((item, fetch));
+    }
+
+    async fn fetch_selected_season_names(&mut self) {
+        let Some(series_id) = self
+            .selected_item()
+            .filter(|item| item.type_ == "Episode")
+            .and_then(|item| item.series_id)
+            .filter(|series_id| !self.season_names.contains_key(series_id))
+        else {
+            return;
+        };
+
+        // ? on failure episodes fall back to "Season N", without asking again
+        let names = self
+            .jellyfin
+            .fetch_season_names(&series_id)
+            .await
+            .unwrap_or_default();
+        self.season_names.insert(series_id, names);
+    }
+
+    fn count_selected_remaining(&mut self) {
+        let Some(series) = self
+            .selected_item()
+            .filter(|item| item.type_ == "Series" && !self.remaining.contains_key(&item.id))
+        else {
+            return;
+        };
+
+        let unwatched = self.jellyfin.get_unwatched_episodes_from_series(&series.id);
+        self.remaining.insert(
+            series.id,
+            remaining_summary(
+                unwatched.len(),
+                unwatched.iter().map(MediaItem::remaining_ticks).sum(),
+            ),
+        );
+    }
+
+    fn season_title(&self, episode: &MediaItem) -> String {
+        let season = episode.parent_index_number.unwrap_or(0);
+
+        episode
+            .series_id
+            .as_ref()
+            .and_then(|series_id| self.season_names.get(series_id))
+            .and_then(|names| names.get(&season))
+            .cloned()
+            .unwrap_or_else(|| format!("Season {}", season))
+    }
+
+    async fn fetch_selected_still(&mut self) {
+        let Some(picker) = self.picker else {
+            return;
+        };
+
+        if let Some((id, fetch)) = self.still_fetch.take_if(|(_, fetch)| fetch.is_finished()) {
+            // ? failures are cached as well, episodes without a still keep the series info
+            let still = fetch
+                .await
+                .ok()
+                .flatten()
+                .map(|image| picker.new_resize_protocol(image));
+            self.stills.insert(id, still);
+        }
+
+        if self.still_fetch.is_some() {
+            return;
+        }
+
+        let Some(item) = self
+            .selected_item()
+            .filter(|item| item.type_ == "Episode")
+            .filter(|item| !self.stills.contains_key(&item.id))
+        else {
+            return;
+        };
+
+        let image = self.jellyfin.fetch_primary_image(&item.id, 640);
+        let fetch = tokio::spawn(async move { image::load_from_memory(&image.await.ok()?).ok() });
+        self.still_fetch = Some((item.id, fetch));
+    }
+
+    fn remember_query(&mut self) {
+        let query = self.query.trim().to_string();
+
+        if query.is_empty() {
+            return;
+        }
+
+        self.search_history.retain(|previous| *previous != query);
+        self.search_history.push(query);
+
+        if self.search_history.len() > SEARCH_HISTORY_LENGTH {
+            self.search_history.remove(0);
+        }
+    }
+
+    // ? older goes back in the history, newer forward and past the newest query back to an empty search
+    fn recall_query(&mut self, older: bool) {
+        let index = match (self.history_index, older) {
+            (None, true) => self.search_history.len().checked_sub(1),
+            (None, false) => return,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) => Some(index + 1).filter(|i| *i < self.search_history.len()),
+        };
+
+        let Some(index) = index else {
+            self.history_index = None;
+            self.clear_search();
+            return;
+        };
+
+        self.history_index = Some(index);
+        self.query = self.search_history[index].clone();
+        self.page = Page::All;
+        self.selection_state = SelectionState::Main;
+        self.set_index(0);
+        self.search();
+    }
+
+    fn home_pages(&self) -> Vec<Page> {
+        enabled_pages(&self.jellyfin.config().enabled_pages)
+    }
+
+    fn page_title(&self, page: Page) -> String {
+        let library = self
+            .latest_library
+            .as_deref()
+            .filter(|_| page == Page::LatestAdded)
+            .and_then(|id| {
+                self.jellyfin
+                    .libraries
+                    .iter()
+                    .find(|library| library.id == id)
+            });
+
+        if let Some(watched) = self
+            .recommended_for
+            .as_ref()
+            .filter(|_| page == Page::Recommended)
+        {
+            return format!("Because you watched {}", watched);
+        }
+
+        match library {
+            Some(library) => format!("{} · {}", page.title(), library.name),
+            None => page.title().to_string(),
+        }
+    }
+
+    fn pages(&self) -> Vec<Page> {
+        if self.page.is_home() {
+            return self.home_pages();
+        }
+
+        let mut pages = vec![Page::All, Page::Movies, Page::Series];
+        if self.config.include_episodes {
+            pages.push(Page::Episodes);
+        }
+
+        pages
+    }
+
+    fn rotate_page(&mut self, forward: bool) {
+        let pages = self.pages();
+        let index = pages
+            .iter()
+            .position(|page| *page == self.page)
+            .unwrap_or(0);
+
+        self.page = if forward {
+            pages[(index + 1) % pages.len()]
+        } else {
+            pages[(index + pages.len() - 1) % pages.len()]
+        };
+
+        self.search();
+    }
+
+    fn jump_to_page(&mut self, number: usize) {
+        let Some(page) = number
+            .checked_sub(1)
+            .and_then(|index| self.home_pages().get(index).copied())
+        else {
+            return;
+        };
+
+        self.page = page;
+        self.search();
+    }
+
+    fn clear_search(&mut self) {
+        self.remember_query();
+        self.query.clear();
+        self.page = self.home_pages()[0];
+        self.set_index(0);
+        self.selection_state = SelectionState::Main;
+        self.filtered.clear();
+    }
+
+    fn jump_to_letter(&mut self, letter: char) {
+        let options = self.selection_options(None);
+        let letter = letter.to_lowercase().collect::<String>();
+
+        // ? start after the current item so repeated presses cycle through the matches
+        let Some(index) = (1..=options.len())
+            .map(|offset| (self.index(None) + offset) % options.len())
+            .find(|&index| options[index].name.to_lowercase().starts_with(&letter))
+        else {
+            return;
+        };
+
+        self.set_index(index);
+    }
+
+    fn search(&mut self) {
+        let mut all;
+        let pool = match self.page {
+            Page::All => {
+                all = self.movies.clone();
+                all.extend(self.series.clone());
+                if self.config.include_episodes {
+                    all.extend(self.episodes.clone());
+                }
+                &all
+            }
+            Page::Movies => &self.movies,
+            Page::Series => &self.series,
+            Page::Episodes => &self.episodes,
+            _ => return,
+        };
+
+        let (text, filters) = parse_query(&self.query);
+
+        let pool: Vec<MediaItem> = pool
+            .iter()
+            .filter(|item| filters.iter().all(|filter| filter.matches(item)))
+            .cloned()
+            .collect();
+
+        let weights = &self.jellyfin.config().search_weights;
+
+        let recent: HashSet<&str> = self
+            .jellyfin
+            .continue_watching
+            .iter()
+            .chain(&self.jellyfin.next_up)
+            .flat_map(|item| [Some(item.id.as_str()), item.series_id.as_deref()])
+            .flatten()
+            .collect();
+
+        let config = self.jellyfin.config();
+        self.filtered = search_filter(
+            &pool,
+            &text,
+            config.search_matcher,
+            config.min_search_score,
+            |item| {
+                let mut weight = 0;
+
+                if item.type_ != "Episode" {
+                    weight += weights.type_boost;
+                }
+
+                if recent.contains(item.id.as_str()) {
+                    weight += weights.recent_boost;
+                }
+
+                weight
+            },
+        );
+    }
+
+    fn draw(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        render_outer: impl Fn(&mut Frame) -> Rect,
+    ) -> Result<()> {
+        let info_panel_percent = self.jellyfin.config().layout.info_panel_percent();
+
+        terminal.draw(|frame| {
+            let inner_area = render_outer(frame);
+
+            let toggles = self.active_toggles();
+            let status_height = if toggles.is_empty() { 0 } else { 1 };
+            let outer_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(status_height)])
+                .split(inner_area);
+
+            let main_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(info_panel_percent),
+                    Constraint::Percentage(100 - info_panel_percent),
+                ])
+                .split(outer_chunks[0]);
+
+            if info_panel_percent > 0 {
+                let selected = self
+                    .selected_item()
+                    .map(|item| self.details.get(&item.id).cloned().unwrap_or(item));
+                self.draw_media_panel(frame, main_chunks[0], selected);
+            }
+
+            let right_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(main_chunks[1]);
+
+            let right_top_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(right_chunks[0]);
+
+            self.draw_search_bar(frame, right_top_chunks[0]);
+
+            match &self.selection_state {
+                SelectionState::Main => {
+                    self.episode_selection.area = Rect::default();
 
-            match &self.selection_state {
-                SelectionState::Main => {
                     // merge right chunks into one for single page
                     // ? right chunks are split beforehand so that they stay aligned with the left panel
                     self.draw_main(
@@ -273,56 +1439,916 @@ impl App {
                         SelectionState::Main,
                     )
                 }
-                SelectionState::Episode => {
-                    self.draw_main(frame, right_top_chunks[1], SelectionState::Main);
-                    self.draw_main(frame, right_chunks[1], SelectionState::Episode);
+                SelectionState::Episode => {
+                    self.draw_main(frame, right_top_chunks[1], SelectionState::Main);
+                    self.draw_main(frame, right_chunks[1], SelectionState::Episode);
+                }
+            }
+
+            let status = Paragraph::new(
+                toggles
+                    .iter()
+                    .map(|toggle| format!("[{}]", toggle))
+                    .join(" "),
+            )
+            .style(self.theme.text());
+            frame.render_widget(status, outer_chunks[1]);
+
+            self.draw_action(frame, inner_area);
+        })?;
+
+        Ok(())
+    }
+
+    fn active_toggles(&self) -> Vec<&'static str> {
+        let mut toggles = Vec::new();
+
+        if self.config.include_episodes {
+            toggles.push("episodes");
+        }
+
+        if self.config.next_up_by_last_watched {
+            toggles.push("next up: last watched");
+        }
+
+        if self.jellyfin.config().latest_added_sort == LatestAddedSort::PremiereDate {
+            toggles.push("latest: premiere date");
+        }
+
+        if self.config.episodes_newest_first {
+            toggles.push("episodes: newest first");
+        }
+
+        toggles
+    }
+
+    fn activate_selected(&mut self) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+
+        self.remember_query();
+
+        let children = match item.type_.as_str() {
+            "Series" => self.episode_list(self.jellyfin.get_episodes_from_series(&item.id)),
+            "MusicArtist" => self.jellyfin.get_albums_from_artist(&item.id),
+            "MusicAlbum" => self.jellyfin.get_tracks_from_album(&item.id),
+            "Playlist" => self.jellyfin.get_playlist_items(&item.id),
+            _ if !item.is_playable() => {
+                self.current_action =
+                    Action::Error(format!("{} is not available for playback", item.name));
+                return;
+            }
+            // ? a watched item can still have a position near its end, which resuming would jump to
+            _ if item.user_data.as_ref().is_some_and(|data| data.played) => {
+                self.current_action = match self.jellyfin.config().rewatch {
+                    Rewatch::Resume => Action::NowPlaying(Box::new(item), None, None),
+                    Rewatch::Beginning => Action::NowPlaying(Box::new(item), None, Some(0)),
+                    Rewatch::Ask => Action::ConfirmingRewatch(Box::new(item)),
+                };
+                return;
+            }
+            _ => {
+                self.current_action = Action::NowPlaying(Box::new(item.clone()), None, None);
+                return;
+            }
+        };
+
+        if item.type_ == "Series" && children.is_empty() {
+            self.current_action = Action::LoadingEpisodes(Box::new(item.clone()));
+        }
+
+        self.selection_state = SelectionState::Episode;
+        self.episode_selection.index = 0;
+        self.episode_selection.series = Some(item.clone());
+        self.episode_selection.episodes = Some(children);
+    }
+
+    // ? only the list is shown newest first, queues and the next episode keep to airing order
+    fn episode_list(&self, mut episodes: Vec<MediaItem>) -> Vec<MediaItem> {
+        if self.config.episodes_newest_first {
+            episodes.reverse();
+        }
+        episodes
+    }
+
+    fn open_menu(&mut self) {
+        if let Some(item) = self.selected_item() {
+            self.current_action = Action::ShowingMenu(Box::new(item), 0);
+        }
+    }
+
+    fn menu_entries(&self, item: &MediaItem) -> Vec<MenuEntry> {
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let playable = matches!(item.type_.as_str(), "Movie" | "Episode" | "Audio");
+
+        let mut entries = Vec::new();
+
+        match item.type_.as_str() {
+            "Series" => {
+                entries.push(MenuEntry::key("Episodes", "Enter", KeyCode::Enter, none));
+                entries.push(MenuEntry::key(
+                    "Play next unwatched",
+                    "Alt + Enter",
+                    KeyCode::Enter,
+                    KeyModifiers::ALT,
+                ));
+                entries.push(MenuEntry::key(
+                    "Play unwatched as playlist",
+                    "Ctrl + p",
+                    KeyCode::Char('p'),
+                    ctrl,
+                ));
+                entries.push(MenuEntry::key(
+                    "Shuffle",
+                    "Ctrl + s",
+                    KeyCode::Char('s'),
+                    ctrl,
+                ));
+            }
+            _ if playable && item.is_playable() => {
+                entries.push(MenuEntry::key("Play", "Enter", KeyCode::Enter, none));
+                entries.push(MenuEntry::key(
+                    "Play from...",
+                    "Ctrl + t",
+                    KeyCode::Char('t'),
+                    ctrl,
+                ));
+            }
+            _ if !playable => {
+                entries.push(MenuEntry::key("Open", "Enter", KeyCode::Enter, none));
+            }
+            _ => {}
+        }
+
+        if item.type_ != "Playlist" {
+            if !item.user_data.as_ref().is_some_and(|data| data.played) {
+                entries.push(MenuEntry {
+                    label: "Mark as played".to_string(),
+                    hint: "",
+                    command: MenuCommand::MarkPlayed,
+                });
+            }
+
+            let favorite = item.user_data.as_ref().is_some_and(|data| data.is_favorite);
+            entries.push(MenuEntry::key(
+                if favorite {
+                    "Remove from favorites"
+                } else {
+                    "Add to favorites"
+                },
+                "Ctrl + b",
+                KeyCode::Char('b'),
+                ctrl,
+            ));
+
+            if !self.jellyfin.playlists.is_empty() {
+                entries.push(MenuEntry::key(
+                    "Add to playlist",
+                    "Ctrl + a",
+                    KeyCode::Char('a'),
+                    ctrl,
+                ));
+            }
+        }
+
+        if item.trailer_url().is_some() {
+            entries.push(MenuEntry::key(
+                "Play trailer",
+                "Ctrl + v",
+                KeyCode::Char('v'),
+                ctrl,
+            ));
+        }
+
+        if playable {
+            entries.push(MenuEntry::key(
+                "Copy stream URL",
+                "Ctrl + y",
+                KeyCode::Char('y'),
+                ctrl,
+            ));
+        }
+
+        if item.overview.is_some() {
+            entries.push(MenuEntry::key("Read overview", "F2", KeyCode::F(2), none));
+        }
+
+        entries.push(MenuEntry::key(
+            "Open in Jellyfin",
+            "Ctrl + o",
+            KeyCode::Char('o'),
+            ctrl,
+        ));
+
+        if item.external_url().is_some() {
+            entries.push(MenuEntry::key(
+                "Open on IMDb/TMDb",
+                "Ctrl + g",
+                KeyCode::Char('g'),
+                ctrl,
+            ));
+        }
+
+        entries.push(MenuEntry::key(
+            "Refresh metadata",
+            "Ctrl + f",
+            KeyCode::Char('f'),
+            ctrl,
+        ));
+
+        if self.jellyfin.can_delete() {
+            entries.push(MenuEntry::key(
+                "Delete",
+                "Shift + Delete",
+                KeyCode::Delete,
+                KeyModifiers::SHIFT,
+            ));
+        }
+
+        entries
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) -> Result<()> {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(arboard::Clipboard::new()?),
+        };
+
+        Ok(clipboard.set_text(text)?)
+    }
+
+    fn request_refresh(&mut self) {
+        self.current_action = if self.jellyfin.config().confirm_refresh {
+            Action::ConfirmingRefresh
+        } else {
+            Action::RefreshingCache
+        };
+    }
+
+    // ? without cached episodes this opens the (empty) episode list like enter would
+    fn play_next_unwatched(&mut self) {
+        let Some(series) = self.selected_item().filter(|item| item.type_ == "Series") else {
+            return self.activate_selected();
+        };
+
+        match next_unwatched(&self.jellyfin.get_episodes_from_series(&series.id)) {
+            Some(episode) => {
+                self.remember_query();
+                self.current_action = Action::NowPlaying(Box::new(episode), None, None);
+            }
+            None => self.activate_selected(),
+        }
+    }
+
+    // ? the offset belongs to the selected item, so selecting another one starts at the top again
+    fn scroll_info(&mut self, lines: i16) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+
+        let offset = self
+            .info_scroll
+            .as_ref()
+            .filter(|(id, _)| *id == item.id)
+            .map_or(0, |(_, offset)| *offset);
+
+        self.info_scroll = Some((item.id, offset.saturating_add_signed(lines)));
+    }
+
+    fn shuffle(&mut self) {
+        let series = match self.selection_state {
+            SelectionState::Main => self.selected_item(),
+            SelectionState::Episode => self.episode_selection.series.clone(),
+        }
+        .filter(|series| series.type_ == "Series");
+
+        let pick = match series {
+            Some(series) => shuffle_pick(
+                &self.jellyfin.get_episodes_from_series(&series.id),
+                &mut self.rng,
+            ),
+            None => {
+                let playable: Vec<_> = self
+                    .selection_options(None)
+                    .iter()
+                    .filter(|item| matches!(item.type_.as_str(), "Movie" | "Episode" | "Audio"))
+                    .cloned()
+                    .collect();
+
+                playable.choose(&mut self.rng).cloned()
+            }
+        };
+
+        if let Some(item) = pick {
+            self.current_action = Action::ConfirmingShuffle(Box::new(item));
+        }
+    }
+
+    fn list_at(&self, column: u16, row: u16) -> Option<SelectionState> {
+        let position = Position::new(column, row);
+
+        [SelectionState::Main, SelectionState::Episode]
+            .into_iter()
+            .find(|state| self.selection(state).area.contains(position))
+    }
+
+    fn selection(&self, state: &SelectionState) -> &Selection {
+        match state {
+            SelectionState::Main => &self.main_selection,
+            SelectionState::Episode => &self.episode_selection,
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if !matches!(self.current_action, Action::None) {
+            return;
+        }
+
+        let Some(state) = self.list_at(mouse.column, mouse.row) else {
+            return;
+        };
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp if state == self.selection_state => {
+                self.set_index(self.index(None).saturating_sub(1));
+            }
+            MouseEventKind::ScrollDown if state == self.selection_state => {
+                let last_index = self.selection_options(None).len().saturating_sub(1);
+                self.set_index((self.index(None) + 1).min(last_index));
+            }
+            MouseEventKind::Down(button @ (MouseButton::Left | MouseButton::Right)) => {
+                let selection = self.selection(&state);
+
+                // ? the first row of the area is the block's border
+                let Some(row) = (mouse.row - selection.area.y).checked_sub(1) else {
+                    return;
+                };
+
+                if row as usize >= selection.visible_height {
+                    return;
+                }
+
+                let index = selection.scroll_position + row as usize;
+
+                if index >= self.selection_options(Some(&state)).len() {
+                    return;
+                }
+
+                // clicking the series list while browsing episodes goes back to it
+                if state == SelectionState::Main && self.selection_state == SelectionState::Episode
+                {
+                    self.selection_state = SelectionState::Main;
+                    self.episode_selection.series = None;
+                    self.episode_selection.episodes = None;
+                }
+
+                self.set_index(index);
+
+                if button == MouseButton::Right {
+                    self.last_click = None;
+                    self.open_menu();
+                    return;
+                }
+
+                let double_click = self
+                    .last_click
+                    .is_some_and(|(time, last_state, last_index)| {
+                        time.elapsed() < Duration::from_millis(500)
+                            && last_state == state
+                            && last_index == index
+                    });
+
+                if double_click {
+                    self.last_click = None;
+                    self.activate_selected();
+                } else {
+                    self.last_click = Some((Instant::now(), state, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_server_events(&mut self) {
+        // ? events wait in the channel until the running update is done
+        if self.library_update.is_some() {
+            return;
+        }
+
+        let mut changed = Vec::new();
+        let mut removed = Vec::new();
+
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                ServerEvent::LibraryChanged {
+                    changed: ids,
+                    removed: removed_ids,
+                } => {
+                    changed.extend(ids);
+                    removed.extend(removed_ids);
+                }
+            }
+        }
+
+        if changed.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let mut jellyfin = self.jellyfin.clone();
+        let update = tokio::spawn(async move { jellyfin.fetch_items_by_id(&changed).await });
+        self.library_update = Some((removed, update));
+    }
+
+    async fn check_library_update(&mut self) {
+        if !self
+            .library_update
+            .as_ref()
+            .is_some_and(|(_, update)| update.is_finished())
+        {
+            return;
+        }
+
+        let Some((removed, update)) = self.library_update.take() else {
+            return;
+        };
+
+        let result = match update.await {
+            Ok(result) => result,
+            Err(e) => Err(e.into()),
+        };
+
+        match result.and_then(|changed| self.jellyfin.apply_library_changes(changed, &removed)) {
+            Ok(()) => self.load_items(),
+            Err(e) => {
+                self.current_action = Action::Error(format!("Failed to update the library: {}", e))
+            }
+        }
+    }
+
+    fn handle_input(&mut self) -> Result<bool> {
+        // wake up regularly so server events and background updates show without waiting for a key press
+        if !poll(self.jellyfin.config().tick_rate())? {
+            return Ok(true);
+        }
+
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) => {
+                self.handle_mouse(mouse);
+                return Ok(true);
+            }
+            _ => return Ok(true),
+        };
+
+        self.handle_key(key)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if let Action::ShowingMenu(item, index) = &self.current_action {
+            let (item, index) = (item.clone(), *index);
+            let entries = self.menu_entries(&item);
+
+            self.current_action = match key.code {
+                KeyCode::Up => Action::ShowingMenu(item, index.saturating_sub(1)),
+                KeyCode::Down => Action::ShowingMenu(item, (index + 1).min(entries.len() - 1)),
+                KeyCode::Enter => match entries[index].command {
+                    MenuCommand::Key(code, modifiers) => {
+                        self.current_action = Action::None;
+
+                        // ? the menu is about this one item, not the marked ones
+                        let marked = std::mem::take(&mut self.selected_ids);
+                        let result = self.handle_key(KeyEvent::new(code, modifiers));
+                        self.selected_ids.extend(marked);
+                        return result;
+                    }
+                    MenuCommand::MarkPlayed => {
+                        self.current_action = Action::None;
+                        self.mark_played(vec![*item]);
+                        return Ok(true);
+                    }
+                },
+                _ => Action::None,
+            };
+            return Ok(true);
+        }
+
+        if let Action::ReadingOverview(_, scroll) = &mut self.current_action {
+            match key.code {
+                KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                KeyCode::Down => *scroll = scroll.saturating_add(1),
+                _ => self.current_action = Action::None,
+            }
+            return Ok(true);
+        }
+
+        if let Action::ChoosingSource(item, sources, index, start_ticks) = &mut self.current_action
+        {
+            match key.code {
+                KeyCode::Up => *index = index.saturating_sub(1),
+                KeyCode::Down => *index = (*index + 1).min(sources.len() - 1),
+                KeyCode::Enter => {
+                    self.current_action = Action::NowPlaying(
+                        item.clone(),
+                        Some(sources[*index].clone()),
+                        *start_ticks,
+                    );
+                }
+                KeyCode::Esc => self.current_action = Action::None,
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        if let Action::EnteringStart(item, input) = &mut self.current_action {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    self.current_action = match parse_start(input, item.runtime_ticks) {
+                        Some(ticks) => Action::NowPlaying(item.clone(), None, Some(ticks)),
+                        None => Action::Error(format!("Invalid start position: {}", input)),
+                    };
+                }
+                KeyCode::Esc => self.current_action = Action::None,
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        if let Action::ChoosingPlaylist(items, index) = &mut self.current_action {
+            let playlists = &self.jellyfin.playlists;
+            match key.code {
+                KeyCode::Up => *index = index.saturating_sub(1),
+                KeyCode::Down => *index = (*index + 1).min(playlists.len() - 1),
+                KeyCode::Enter => {
+                    self.current_action = Action::AddingToPlaylist(
+                        items.clone(),
+                        Box::new(playlists[*index].clone()),
+                    );
+                    self.selected_ids.clear();
+                }
+                KeyCode::Esc => self.current_action = Action::None,
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        if let Action::ChoosingLibrary(index) = &mut self.current_action {
+            let libraries = &self.jellyfin.libraries;
+            match key.code {
+                KeyCode::Up => *index = index.saturating_sub(1),
+                KeyCode::Down => *index = (*index + 1).min(libraries.len()),
+                KeyCode::Enter => {
+                    self.latest_library = index
+                        .checked_sub(1)
+                        .map(|index| libraries[index].id.clone());
+                    self.current_action = Action::None;
+
+                    self.query.clear();
+                    self.filtered.clear();
+                    self.selection_state = SelectionState::Main;
+                    self.page = Page::LatestAdded;
+                    self.set_index(0);
+                }
+                KeyCode::Esc => self.current_action = Action::None,
+                _ => {}
+            }
+            return Ok(true);
+        }
+
+        if let Action::ConfirmingRefresh = self.current_action {
+            self.current_action = match key.code {
+                KeyCode::Enter => Action::RefreshingCache,
+                _ => Action::None,
+            };
+            return Ok(true);
+        }
+
+        if let Action::ConfirmingShuffle(item) = &self.current_action {
+            self.current_action = match key.code {
+                KeyCode::Enter => Action::NowPlaying(item.clone(), None, None),
+                _ => Action::None,
+            };
+            return Ok(true);
+        }
+
+        if let Action::ConfirmingRewatch(item) = &self.current_action {
+            self.current_action = match key.code {
+                KeyCode::Enter => Action::NowPlaying(item.clone(), None, Some(0)),
+                KeyCode::Char('r') => Action::NowPlaying(item.clone(), None, None),
+                _ => Action::None,
+            };
+            return Ok(true);
+        }
+
+        if let Action::ConfirmingDelete(item) = &self.current_action {
+            self.current_action = match key.code {
+                KeyCode::Char('y') => Action::Deleting(item.clone()),
+                _ => Action::None,
+            };
+            return Ok(true);
+        }
+
+        // any key dismisses a summary or error popup
+        if let Action::RefreshComplete(_)
+        | Action::ShowingStats(_)
+        | Action::Copied(_)
+        | Action::Error(_) = self.current_action
+        {
+            self.current_action = Action::None;
+            return Ok(true);
+        }
+
+        if self.vim_keys_active() && self.handle_vim_key(&key) {
+            return Ok(true);
+        }
+
+        match key.code {
+            // ! make F1 show help
+            KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                return Ok(false);
+            }
+            KeyCode::F(5) => {
+                self.request_refresh();
+            }
+            // ? after watching elsewhere only the home page is out of date, which is much quicker to fetch
+            KeyCode::F(6) => {
+                self.current_action = Action::RefreshingHome;
+            }
+            KeyCode::Char('r') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.request_refresh();
+            }
+            KeyCode::Char('f') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                if let Some(item) = self.selected_item() {
+                    self.current_action = Action::RefreshingItem(Box::new(item));
+                }
+            }
+            KeyCode::Char('e') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.config.include_episodes = !self.config.include_episodes;
+
+                if self.page == Page::Episodes {
+                    self.page = Page::All;
+                }
+
+                if self.page == Page::All {
+                    self.search();
+                }
+            }
+            KeyCode::F(4) => {
+                self.config.episodes_newest_first = !self.config.episodes_newest_first;
+
+                // ? an open episode list is flipped in place, keeping the same episode selected
+                if let (Some(episodes), Some(series)) = (
+                    &mut self.episode_selection.episodes,
+                    &self.episode_selection.series,
+                ) {
+                    if series.type_ == "Series" && !episodes.is_empty() {
+                        episodes.reverse();
+                        self.episode_selection.index = episodes.len()
+                            - 1
+                            - self.episode_selection.index.min(episodes.len() - 1);
+                    }
+                }
+            }
+            KeyCode::Char('n') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.config.next_up_by_last_watched = !self.config.next_up_by_last_watched;
+                self.load_items();
+            }
+            KeyCode::Backspace | KeyCode::Char('h')
+                if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+            {
+                // ? ctrl+h is backspace on some terminals
+                self.clear_search();
+            }
+            KeyCode::Char('p') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let series = match self.selection_state {
+                    SelectionState::Main => self.selected_item(),
+                    SelectionState::Episode => self.episode_selection.series.clone(),
+                };
+
+                let Some(series) = series.filter(|series| series.type_ == "Series") else {
+                    return Ok(true);
+                };
+
+                let queue = self.jellyfin.get_unwatched_episodes_from_series(&series.id);
+
+                if !queue.is_empty() {
+                    self.current_action = Action::PlayingQueue(queue);
+                }
+            }
+            KeyCode::Char('j') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.scroll_info(1);
+            }
+            KeyCode::Char('k') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.scroll_info(-1);
+            }
+            KeyCode::Char('o') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let Some(item) = self.selected_item() else {
+                    return Ok(true);
+                };
+
+                if let Err(e) = open::that_detached(self.jellyfin.web_url(&item)) {
+                    self.current_action = Action::Error(format!("Could not open a browser: {}", e));
                 }
             }
+            KeyCode::Char('g') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let Some(item) = self.selected_item() else {
+                    return Ok(true);
+                };
 
-            self.draw_action(frame, inner_area);
-        })?;
+                let Some(url) = item.external_url() else {
+                    self.current_action =
+                        Action::Error(format!("{} has no IMDb or TMDb id", item.name));
+                    return Ok(true);
+                };
 
-        Ok(())
-    }
+                if let Err(e) = open::that_detached(url) {
+                    self.current_action = Action::Error(format!("Could not open a browser: {}", e));
+                }
+            }
+            KeyCode::Char('v') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let Some(item) = self.selected_item() else {
+                    return Ok(true);
+                };
 
-    fn handle_input(&mut self) -> Result<bool> {
-        let Event::Key(key) = event::read()? else {
-            return Ok(true);
-        };
+                if item.trailer_url().is_none() {
+                    self.current_action = Action::Error("No trailer available".to_string());
+                    return Ok(true);
+                }
 
-        match key.code {
-            // ! make F1 show help
-            KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                return Ok(false);
+                if let Err(e) = self.jellyfin.play_trailer(&item) {
+                    self.current_action = Action::Error(e.to_string());
+                }
             }
-            KeyCode::F(5) => {
-                self.current_action = Action::RefreshingCache;
+            KeyCode::Char('y') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                if let Some(item) = self
+                    .selected_item()
+                    .filter(|item| matches!(item.type_.as_str(), "Movie" | "Episode" | "Audio"))
+                {
+                    self.current_action = Action::CopyingUrl(Box::new(item));
+                }
             }
-            KeyCode::Char('r') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                self.current_action = Action::RefreshingCache;
+            KeyCode::Char('x') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                // ? only a detached player or a trailer can be running while keys are handled
+                if let Err(e) = self.jellyfin.stop_playback() {
+                    self.current_action = Action::Error(format!("Failed to stop playback: {}", e));
+                }
             }
-            KeyCode::Char('e') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                self.config.include_episodes = !self.config.include_episodes;
+            KeyCode::Char('s') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.shuffle();
+            }
+            KeyCode::Char('a') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let items: Vec<_> = self
+                    .batch_items()
+                    .into_iter()
+                    .filter(|item| item.type_ != "Playlist")
+                    .collect();
 
-                if self.page == Page::Episodes {
-                    self.page = Page::All;
+                if items.is_empty() {
+                    return Ok(true);
                 }
 
-                if self.page == Page::All {
-                    self.search();
+                self.current_action = if self.jellyfin.playlists.is_empty() {
+                    Action::Error("No playlists yet, create one in Jellyfin first".to_string())
+                } else {
+                    Action::ChoosingPlaylist(items, 0)
+                };
+            }
+            KeyCode::Char('b') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let items = self.batch_items();
+
+                if items.is_empty() {
+                    return Ok(true);
                 }
+
+                self.selected_ids.clear();
+
+                let favorite = !items
+                    .iter()
+                    .all(|item| item.user_data.as_ref().is_some_and(|data| data.is_favorite));
+
+                let changed = items
+                    .into_iter()
+                    .filter(|item| {
+                        item.user_data.as_ref().is_some_and(|data| data.is_favorite) != favorite
+                    })
+                    .collect();
+
+                self.current_action = Action::Favoriting(Batch::new(changed), favorite);
             }
-            KeyCode::Backspace | KeyCode::Char('h')
-                if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
+            KeyCode::Char('z') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.current_action = match self.undo_stack.pop() {
+                    Some(Toggle::Played(items)) => Action::MarkingPlayed(Batch::undo(items), false),
+                    Some(Toggle::Favorite(items, favorite)) => {
+                        Action::Favoriting(Batch::undo(items), !favorite)
+                    }
+                    None => Action::Error("Nothing to undo".to_string()),
+                };
+            }
+            KeyCode::Char('w')
+                if key.modifiers.contains(event::KeyModifiers::CONTROL)
+                    && !self.selected_ids.is_empty() =>
             {
-                // ? ctrl+h is backspace on some terminals
-                self.query.clear();
-                self.page = Page::ContinueWatching;
-                self.set_index(0);
-                self.selection_state = SelectionState::Main;
-                self.filtered.clear();
+                let items = self.batch_items();
+                self.selected_ids.clear();
+                self.mark_played(items);
+            }
+            KeyCode::Char('q') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                if !self.home_pages().contains(&Page::LatestAdded) {
+                    return Ok(true);
+                }
+
+                self.current_action = if self.jellyfin.libraries.is_empty() {
+                    Action::Error("No libraries found".to_string())
+                } else {
+                    let index = self
+                        .latest_library
+                        .as_deref()
+                        .and_then(|id| {
+                            self.jellyfin
+                                .libraries
+                                .iter()
+                                .position(|library| library.id == id)
+                        })
+                        .map_or(0, |index| index + 1);
+
+                    Action::ChoosingLibrary(index)
+                };
+            }
+            KeyCode::Tab => {
+                self.open_menu();
+            }
+            KeyCode::F(2) => {
+                if let Some(item) = self.selected_item() {
+                    self.current_action = Action::ReadingOverview(Box::new(item), 0);
+                }
+            }
+            KeyCode::Char('l') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                self.current_action = Action::ShowingStats(self.jellyfin.library_stats());
+            }
+            KeyCode::Char('t') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let Some(item) = self
+                    .selected_item()
+                    .filter(|item| matches!(item.type_.as_str(), "Movie" | "Episode" | "Audio"))
+                    .filter(|item| item.is_playable())
+                else {
+                    return Ok(true);
+                };
+
+                self.current_action = Action::EnteringStart(Box::new(item), String::new());
+            }
+            KeyCode::Char('w') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+                let Some(episode) = self
+                    .selected_item()
+                    .filter(|_| self.selection_state == SelectionState::Episode)
+                    .filter(|item| item.type_ == "Episode")
+                else {
+                    return Ok(true);
+                };
+
+                self.mark_played(episodes_before(self.selection_options(None), &episode));
+            }
+            KeyCode::Delete if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                if !self.jellyfin.can_delete() {
+                    return Ok(true);
+                }
+
+                if let Some(item) = self.selected_item() {
+                    self.current_action = Action::ConfirmingDelete(Box::new(item));
+                }
+            }
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.recall_query(true);
+            }
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.recall_query(false);
+            }
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.jump_to_letter(c);
+            }
+            // ? digits without a page of their own still start a search
+            KeyCode::Char(digit @ '1'..='9')
+                if self.query.is_empty()
+                    && self.selection_state == SelectionState::Main
+                    && digit.to_digit(10).unwrap() as usize <= self.home_pages().len() =>
+            {
+                self.jump_to_page(digit.to_digit(10).unwrap() as usize);
+            }
+            // ? a space only starts marking where it couldn't be part of a search
+            KeyCode::Char(' ')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    || self.query.is_empty()
+                    || self.selection_state == SelectionState::Episode =>
+            {
+                self.toggle_marked();
             }
             KeyCode::Char(c) => {
+                self.history_index = None;
+
                 if self.query.is_empty() {
                     self.page = Page::All;
                 }
@@ -340,29 +2366,53 @@ impl App {
                 if !self.query.is_empty() {
                     self.search();
                 } else {
-                    self.page = Page::ContinueWatching;
+                    self.page = self.home_pages()[0];
                     self.filtered.clear();
                 }
             }
+            // ? not every terminal reports shift with enter, alt works as well
+            KeyCode::Enter
+                if key
+                    .modifiers
+                    .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+            {
+                self.play_next_unwatched();
+            }
             KeyCode::Enter => {
-                let Some(item) = self.selected_item() else {
-                    return Ok(true);
-                };
+                self.activate_selected();
+            }
+            KeyCode::Esc if !self.selected_ids.is_empty() => {
+                self.selected_ids.clear();
+            }
+            KeyCode::Esc => {
+                if self.selection_state == SelectionState::Main {
+                    if self.query.is_empty() {
+                        return Ok(false);
+                    }
 
-                if item.type_ != "Series" {
-                    self.current_action = Action::NowPlaying(item.clone());
+                    self.clear_search();
                     return Ok(true);
                 }
 
-                self.selection_state = SelectionState::Episode;
-                self.episode_selection.series = Some(item.clone());
-                self.episode_selection.episodes =
-                    Some(self.jellyfin.get_episodes_from_series(&item.id));
-            }
-            KeyCode::Esc => {
-                if self.selection_state == SelectionState::Main {
-                    return Ok(false);
+                if let Some(album) = self
+                    .episode_selection
+                    .series
+                    .as_ref()
+                    .filter(|series| series.type_ == "MusicAlbum")
+                {
+                    if let Some(artist) = self
+                        .selected_item_in(&SelectionState::Main)
+                        .filter(|artist| artist.type_ == "MusicArtist")
+                    {
+                        let albums = self.jellyfin.get_albums_from_artist(&artist.id);
+                        self.episode_selection.index =
+                            albums.iter().position(|a| a.id == album.id).unwrap_or(0);
+                        self.episode_selection.series = Some(artist);
+                        self.episode_selection.episodes = Some(albums);
+                        return Ok(true);
+                    }
                 }
+
                 self.set_index(0);
                 self.selection_state = SelectionState::Main;
                 self.episode_selection.series = None;
@@ -371,10 +2421,8 @@ impl App {
             KeyCode::Up => {
                 self.set_index(self.index(None).saturating_sub(1));
             }
-            KeyCode::Down => {
-                if self.index(None) < self.selection_options(None).len() - 1 {
-                    self.set_index(self.index(None) + 1);
-                }
+            KeyCode::Down if self.index(None) + 1 < self.selection_options(None).len() => {
+                self.set_index(self.index(None) + 1);
             }
             KeyCode::PageUp => {
                 self.set_index(
@@ -393,53 +2441,14 @@ impl App {
                     return Ok(true);
                 }
 
-                match self.page {
-                    Page::ContinueWatching => self.page = Page::AllSeries,
-                    Page::NextUp => self.page = Page::ContinueWatching,
-                    Page::LatestAdded => self.page = Page::NextUp,
-                    Page::AllMovies => self.page = Page::LatestAdded,
-                    Page::AllSeries => self.page = Page::AllMovies,
-                    Page::All => {
-                        self.page = {
-                            if self.config.include_episodes {
-                                Page::Episodes
-                            } else {
-                                Page::Series
-                            }
-                        }
-                    }
-                    Page::Movies => self.page = Page::All,
-                    Page::Series => self.page = Page::Movies,
-                    Page::Episodes => self.page = Page::Series,
-                }
-                self.search();
+                self.rotate_page(false);
             }
             KeyCode::Right => {
                 if self.selection_state != SelectionState::Main {
                     return Ok(true);
                 }
 
-                match self.page {
-                    Page::ContinueWatching => self.page = Page::NextUp,
-                    Page::NextUp => self.page = Page::LatestAdded,
-                    Page::LatestAdded => self.page = Page::AllMovies,
-                    Page::AllMovies => self.page = Page::AllSeries,
-                    Page::AllSeries => self.page = Page::ContinueWatching,
-                    Page::All => self.page = Page::Movies,
-                    Page::Movies => self.page = Page::Series,
-                    Page::Series => {
-                        self.page = {
-                            if self.config.include_episodes {
-                                Page::Episodes
-                            } else {
-                                Page::All
-                            }
-                        }
-                    }
-                    Page::Episodes => self.page = Page::All,
-                }
-
-                self.search();
+                self.rotate_page(true);
             }
             _ => {}
         }
@@ -447,19 +2456,409 @@ impl App {
         Ok(true)
     }
 
+    // ? vim keys take precedence over search-as-you-type only while the query is empty
+    // ? or while browsing a series' episodes, otherwise letters keep feeding the search
+    fn vim_keys_active(&self) -> bool {
+        self.jellyfin.config().vim_keys
+            && (self.query.is_empty() || self.selection_state == SelectionState::Episode)
+    }
+
+    fn handle_vim_key(&mut self, key: &KeyEvent) -> bool {
+        let last_index = self.selection_options(None).len().saturating_sub(1);
+        let half_page = self.visible_height(None) / 2;
+
+        match key.code {
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.set_index((self.index(None) + half_page).min(last_index));
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.set_index(self.index(None).saturating_sub(half_page));
+            }
+            _ if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            // ? Alt+letter jumps to a title, so it never counts as a vim key
+            _ if key.modifiers.contains(KeyModifiers::ALT) => return false,
+            KeyCode::Char('j') => {
+                self.set_index((self.index(None) + 1).min(last_index));
+            }
+            KeyCode::Char('k') => {
+                self.set_index(self.index(None).saturating_sub(1));
+            }
+            KeyCode::Char('g') => {
+                self.set_index(0);
+            }
+            KeyCode::Char('G') => {
+                self.set_index(last_index);
+            }
+            KeyCode::Char('m') => {
+                self.open_menu();
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    // ? items that already are played are left out, so undoing doesn't unmark them
+    fn mark_played(&mut self, items: Vec<MediaItem>) {
+        let items: Vec<_> = items
+            .into_iter()
+            .filter(|item| !item.user_data.as_ref().is_some_and(|data| data.played))
+            .collect();
+
+        if items.is_empty() {
+            return;
+        }
+
+        self.current_action = Action::MarkingPlayed(Batch::new(items), true);
+    }
+
+    fn remove_deleted(&mut self, item: &MediaItem) {
+        self.load_items();
+        self.filtered.retain(|other| other.id != item.id);
+
+        if self
+            .episode_selection
+            .series
+            .as_ref()
+            .is_some_and(|series| series.id == item.id)
+        {
+            self.selection_state = SelectionState::Main;
+            self.episode_selection.series = None;
+            self.episode_selection.episodes = None;
+        }
+
+        if let Some(episodes) = &mut self.episode_selection.episodes {
+            episodes.retain(|other| other.id != item.id);
+        }
+
+        let last_index = self.selection_options(None).len().saturating_sub(1);
+        self.set_index(self.index(None).min(last_index));
+    }
+
+    fn detach(
+        &mut self,
+        title: String,
+        items: Vec<MediaItem>,
+        playback: Playback,
+    ) -> Result<Action> {
+        self.detached = Some((title, items, playback));
+
+        Ok(Action::None)
+    }
+
+    async fn check_detached_playback(&mut self) {
+        if !self
+            .detached
+            .as_ref()
+            .is_some_and(|(_, _, playback)| playback.is_finished())
+        {
+            return;
+        }
+
+        let Some((_, items, playback)) = self.detached.take() else {
+            return;
+        };
+
+        let (jellyfin, result) = match playback.await {
+            Ok(finished) => finished,
+            Err(e) => {
+                self.current_action = Action::Error(format!("Playback failed: {}", e));
+                return;
+            }
+        };
+
+        let saved = self.jellyfin.take_user_data(&jellyfin, &items);
+        // ? the lists hold copies, which still show the old watched state
+        self.load_items();
+
+        if let Err(e) = result {
+            self.current_action = Action::Error(e.to_string());
+        } else if let Err(e) = saved {
+            self.current_action = Action::Error(format!("Failed to save cache: {}", e));
+        }
+    }
+
     async fn handle_action(&mut self) -> Result<bool> {
-        match &self.current_action {
-            Action::None => return Ok(false),
-            Action::NowPlaying(item) => {
-                self.jellyfin.play_media(item).await?;
+        let result = match &self.current_action {
+            Action::None
+            | Action::ChoosingSource(..)
+            | Action::ChoosingPlaylist(..)
+            | Action::ChoosingLibrary(_)
+            | Action::ConfirmingDelete(_)
+            | Action::ConfirmingShuffle(_)
+            | Action::ConfirmingRewatch(_)
+            | Action::ConfirmingRefresh
+            | Action::EnteringStart(..)
+            | Action::RefreshComplete(_)
+            | Action::ShowingStats(_)
+            | Action::ShowingMenu(..)
+            | Action::ReadingOverview(..)
+            | Action::Copied(_)
+            | Action::Error(_) => return Ok(false),
+            Action::NowPlaying(..) | Action::PlayingQueue(_) if self.detached.is_some() => Ok(
+                Action::Error("Already playing, stop it with Ctrl + x first".to_string()),
+            ),
+            Action::NowPlaying(item, Some(source), start_ticks)
+                if self.jellyfin.config().detached_playback =>
+            {
+                let mut jellyfin = self.jellyfin.clone();
+                let (title, item, source) = (item.name.clone(), (**item).clone(), source.clone());
+                let start_ticks = *start_ticks;
+
+                self.detach(
+                    title,
+                    vec![item.clone()],
+                    tokio::spawn(async move {
+                        let result = jellyfin.play_media(&item, &source, start_ticks).await;
+                        (jellyfin, result)
+                    }),
+                )
             }
-            Action::RefreshingCache => {
-                self.jellyfin.refresh_cache().await?;
-                if self.query.is_empty() {
-                    self.search();
+            Action::NowPlaying(item, Some(source), start_ticks) => {
+                let control = self.jellyfin.playback_control();
+                let playback = self.jellyfin.play_media(item, source, *start_ticks);
+                until_stopped(playback, control).await.map(|_| Action::None)
+            }
+            Action::NowPlaying(item, None, start_ticks) => {
+                match self.jellyfin.fetch_media_sources(item).await {
+                    Ok(sources) if sources.len() > 1 => Ok(Action::ChoosingSource(
+                        item.clone(),
+                        sources,
+                        0,
+                        *start_ticks,
+                    )),
+                    Ok(mut sources) => Ok(Action::NowPlaying(
+                        item.clone(),
+                        sources.pop(),
+                        *start_ticks,
+                    )),
+                    Err(e) => Ok(Action::Error(format!(
+                        "{} is not available for playback: {}",
+                        item.name, e
+                    ))),
                 }
             }
-        }
+            Action::PlayingQueue(queue) if self.jellyfin.config().detached_playback => {
+                let mut jellyfin = self.jellyfin.clone();
+                let queue = queue.clone();
+                let title = queue[0].series_name.clone().unwrap_or_default();
+
+                self.detach(
+                    title,
+                    queue.clone(),
+                    tokio::spawn(async move {
+                        let result = jellyfin.play_queue(&queue).await;
+                        (jellyfin, result)
+                    }),
+                )
+            }
+            Action::PlayingQueue(queue) => {
+                let control = self.jellyfin.playback_control();
+                let playback = self.jellyfin.play_queue(queue);
+                until_stopped(playback, control).await.map(|_| Action::None)
+            }
+            Action::AddingToPlaylist(items, playlist) => {
+                match self.jellyfin.add_to_playlist(playlist, items).await {
+                    Ok(()) => {
+                        // ? the playlist may be the list that is open right now
+                        if let Some(open) = self
+                            .episode_selection
+                            .series
+                            .as_ref()
+                            .filter(|open| open.id == playlist.id)
+                        {
+                            self.episode_selection.episodes =
+                                Some(self.jellyfin.get_playlist_items(&open.id));
+                        }
+                        Ok(Action::None)
+                    }
+                    Err(e) => Ok(Action::Error(format!(
+                        "Failed to add {} to {}: {}",
+                        describe_items(items),
+                        playlist.name,
+                        e
+                    ))),
+                }
+            }
+            Action::Deleting(item) => match self.jellyfin.delete_item(item).await {
+                Ok(()) => {
+                    let item = item.clone();
+                    self.remove_deleted(&item);
+                    Ok(Action::None)
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "Failed to delete {}: {}",
+                    item.name, e
+                ))),
+            },
+            Action::MarkingPlayed(batch, played) => {
+                let item = &batch.pending[0];
+                let result = if *played {
+                    self.jellyfin.mark_played(item).await
+                } else {
+                    self.jellyfin.restore_played(item).await
+                };
+
+                match (result, batch.advance()) {
+                    (Ok(()), Some(next)) => Ok(Action::MarkingPlayed(next, *played)),
+                    (result, _) => {
+                        if let Some(done) = batch.finish(result.is_ok()) {
+                            remember(&mut self.undo_stack, Toggle::Played(done));
+                        }
+
+                        let saved = self.jellyfin.save_cache();
+
+                        if let Some(series) = self
+                            .episode_selection
+                            .series
+                            .as_ref()
+                            .filter(|series| series.type_ == "Series")
+                        {
+                            self.episode_selection.episodes =
+                                Some(self.episode_list(
+                                    self.jellyfin.get_episodes_from_series(&series.id),
+                                ));
+                        }
+                        let action = match (result, saved) {
+                            (Ok(()), Ok(())) => Action::None,
+                            (Ok(()), Err(e)) => {
+                                Action::Error(format!("Failed to save cache: {}", e))
+                            }
+                            (Err(e), _) => Action::Error(format!(
+                                "Failed to mark {} as {}: {}",
+                                item.name,
+                                if *played { "played" } else { "unplayed" },
+                                e
+                            )),
+                        };
+
+                        self.load_items();
+                        Ok(action)
+                    }
+                }
+            }
+            Action::Favoriting(batch, favorite) => {
+                let item = &batch.pending[0];
+
+                match (
+                    self.jellyfin.set_favorite(item, *favorite).await,
+                    batch.advance(),
+                ) {
+                    (Ok(()), Some(next)) => Ok(Action::Favoriting(next, *favorite)),
+                    (result, _) => {
+                        if let Some(done) = batch.finish(result.is_ok()) {
+                            remember(&mut self.undo_stack, Toggle::Favorite(done, *favorite));
+                        }
+
+                        let saved = self.jellyfin.save_cache();
+
+                        let action = match (result, saved) {
+                            (Ok(()), Ok(())) => Action::None,
+                            (Ok(()), Err(e)) => {
+                                Action::Error(format!("Failed to save cache: {}", e))
+                            }
+                            (Err(e), _) => Action::Error(format!(
+                                "Failed to update favorite {}: {}",
+                                item.name, e
+                            )),
+                        };
+
+                        self.load_items();
+                        Ok(action)
+                    }
+                }
+            }
+            Action::CopyingUrl(item) => match self.jellyfin.shareable_stream_url(item).await {
+                Ok(url) => {
+                    let item = item.clone();
+                    match self.copy_to_clipboard(url) {
+                        Ok(()) => Ok(Action::Copied(item)),
+                        Err(e) => Ok(Action::Error(format!("No clipboard available: {}", e))),
+                    }
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "{} is not available for playback: {}",
+                    item.name, e
+                ))),
+            },
+            Action::LoadingEpisodes(series) => match self.jellyfin.fetch_episodes(&series.id).await
+            {
+                Ok(episodes) => {
+                    // ? the list may have been left while loading
+                    if self
+                        .episode_selection
+                        .series
+                        .as_ref()
+                        .is_some_and(|open| open.id == series.id)
+                    {
+                        self.episode_selection.episodes = Some(self.episode_list(episodes));
+                    }
+                    self.load_items();
+                    Ok(Action::None)
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "Failed to load episodes of {}: {}",
+                    series.name, e
+                ))),
+            },
+            Action::RefreshingItem(item) => match self.jellyfin.refresh_item(item).await {
+                Ok(fresh) => {
+                    self.details.remove(&fresh.id);
+                    self.stills.remove(&fresh.id);
+                    self.load_items();
+
+                    if let Some(episodes) = &mut self.episode_selection.episodes {
+                        for episode in episodes.iter_mut().filter(|ep| ep.id == fresh.id) {
+                            *episode = fresh.clone();
+                        }
+                    }
+
+                    if !self.query.is_empty() {
+                        self.search();
+                    }
+
+                    Ok(Action::None)
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "Failed to refresh {}: {}",
+                    item.name, e
+                ))),
+            },
+            Action::RefreshingCache => match self.jellyfin.refresh_cache().await {
+                Ok(summary) => {
+                    self.load_items();
+                    if self.query.is_empty() {
+                        self.search();
+                    }
+                    Ok(Action::RefreshComplete(summary))
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "Failed to refresh the library: {}",
+                    e
+                ))),
+            },
+            Action::RefreshingHome => match self.jellyfin.fetch_home_sections().await {
+                Ok(failed) => {
+                    // ? next up is copied out for sorting, so it has to be rebuilt
+                    self.load_items();
+                    if self.query.is_empty() {
+                        self.search();
+                    }
+                    if failed.is_empty() {
+                        Ok(Action::None)
+                    } else {
+                        Ok(Action::Error(format!(
+                            "Failed to fetch {}",
+                            failed.join(", ")
+                        )))
+                    }
+                }
+                Err(e) => Ok(Action::Error(format!(
+                    "Failed to refresh the home page: {}",
+                    e
+                ))),
+            },
+        };
 
         loop {
             if poll(Duration::from_millis(5))? {
@@ -469,7 +2868,13 @@ impl App {
             }
         }
 
-        self.current_action = Action::None;
+        self.current_action = match result {
+            Err(e) if e.is::<PlayerNotFound>() || e.is::<PlayerFailed>() => {
+                Action::Error(e.to_string())
+            }
+            Err(e) => return Err(e),
+            Ok(action) => action,
+        };
 
         Ok(true)
     }
@@ -485,22 +2890,21 @@ impl App {
             None => {
                 let text = vec![Line::from("No item selected")];
                 let widget = Paragraph::new(text)
-                    .block(Block::default().title("Media Info").borders(Borders::ALL));
+                    .style(self.theme.text())
+                    .block(self.block("Media Info"));
                 return frame.render_widget(widget, chunk);
             }
         };
 
         let mut chunks: std::rc::Rc<[ratatui::prelude::Rect]> = std::rc::Rc::new([chunk]);
 
-        let info_text;
-
-        if item.type_ == "Episode" {
+        let (mut info_text, overview_heading) = if item.type_ == "Episode" {
             chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
                 .split(chunk);
 
-            info_text = vec![
+            let details = vec![
                 Line::from(vec![Span::styled(
                     format!(
                         "S{:02}E{:02} - {}",
@@ -512,25 +2916,63 @@ impl App {
                 )]),
                 Line::from(""),
                 Line::from(item.format_runtime()),
-                Line::from(format!("Ends at {}", item.format_end_time())),
-                Line::from(""),
+                Line::from(format!(
+                    "Ends at {}",
+                    item.format_end_time(self.jellyfin.config().time_format)
+                )),
+            ];
+
+            (
+                details,
+                vec![
+                    Line::from(""),
+                    Line::from(vec![Span::styled(
+                        "Episode Overview",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )]),
+                ],
+            )
+        } else if matches!(item.type_.as_str(), "MusicArtist" | "MusicAlbum" | "Audio") {
+            let details = vec![
                 Line::from(vec![Span::styled(
-                    "Episode Overview",
+                    &item.name,
                     Style::default().add_modifier(Modifier::BOLD),
                 )]),
+                Line::from(""),
+                Line::from(if item.artists.is_empty() {
+                    "Artist unknown".to_string()
+                } else {
+                    item.artists.join(", ")
+                }),
+                Line::from(item.album.clone().unwrap_or_default()),
+                Line::from(
+                    item.year
+                        .map_or("Year unknown".to_string(), |y| y.to_string()),
+                ),
+                Line::from(item.format_runtime()),
             ];
+
+            (
+                details,
+                vec![
+                    Line::from(""),
+                    Line::from(vec![Span::styled(
+                        "Overview",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )]),
+                ],
+            )
         } else {
-            info_text = vec![
+            let details = vec![
                 Line::from(vec![Span::styled(
                     &item.name,
                     Style::default().add_modifier(Modifier::BOLD),
                 )]),
                 Line::from(""),
-                Line::from(format!(
-                    "{}",
+                Line::from(
                     item.year
-                        .map_or("Year unknown".to_string(), |y| y.to_string())
-                )),
+                        .map_or("Year unknown".to_string(), |y| y.to_string()),
+                ),
                 Line::from(item.format_runtime()),
                 Line::from(format!(
                     "IMDb: {}",
@@ -542,33 +2984,91 @@ impl App {
                     item.critic_rating
                         .map_or("N/A".to_string(), |r| format!("{}%", r))
                 )),
-                Line::from(format!("Ends at {}", item.format_end_time())),
-                Line::from(""),
-                Line::from(""),
-                Line::from(vec![Span::styled(
-                    "Overview",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )]),
+                Line::from(format!(
+                    "Ends at {}",
+                    item.format_end_time(self.jellyfin.config().time_format)
+                )),
             ];
+
+            (
+                details,
+                vec![
+                    Line::from(""),
+                    Line::from(""),
+                    Line::from(vec![Span::styled(
+                        "Overview",
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )]),
+                ],
+            )
+        };
+
+        if let Some(remaining) = self.remaining.get(&item.id) {
+            info_text.push(Line::from(remaining.clone()));
         }
 
+        // only for items that have any
+        let ids: Vec<_> = [("IMDb", item.imdb_id()), ("TMDb", item.tmdb_id())]
+            .into_iter()
+            .filter_map(|(name, id)| id.map(|id| format!("{} {}", name, id)))
+            .collect();
+        if !ids.is_empty() && item.type_ != "Audio" {
+            info_text.push(Line::from(ids.join(" · ")));
+        }
+
+        info_text.extend(overview_heading);
+
         let overview = item.overview.as_deref().unwrap_or("No overview available");
-        let max_width = chunks[0].width as usize - 4;
-        let wrapped_overview: Vec<Line> = textwrap::wrap(overview, max_width)
+        let max_width = (chunks[0].width as usize).saturating_sub(4).max(1);
+        let mut wrapped_overview: Vec<Line> = textwrap::wrap(overview, max_width)
             .into_iter()
             .map(|line| Line::from(line.to_string()))
             .collect();
 
+        // ? keeps the rest of the info in view on short terminals, the full text is a key press away
+        if let Some(max_lines) = self.jellyfin.config().max_overview_lines {
+            if wrapped_overview.len() > max_lines {
+                wrapped_overview.truncate(max_lines);
+                wrapped_overview.push(Line::styled(
+                    "… F2 to read more",
+                    Style::default().add_modifier(Modifier::ITALIC),
+                ));
+            }
+        }
+
+        if let Some(devices) = self.playing_elsewhere.get(&item.id) {
+            info_text.insert(
+                1,
+                Line::styled(
+                    format!("▶ on {}", devices.join(", ")),
+                    Style::default().fg(self.theme.selection_fg),
+                ),
+            );
+        }
+
         let mut all_lines = info_text;
         all_lines.extend(wrapped_overview);
 
+        // ? clamped here, where the panel height is known, so scrolling back up responds right away
+        let max_offset = (all_lines.len() as u16)
+            .saturating_sub(chunks.last().unwrap().height.saturating_sub(2));
+        let mut offset = 0;
+        if let Some((id, scroll)) = &mut self.info_scroll {
+            if *id == item.id {
+                *scroll = (*scroll).min(max_offset);
+                offset = *scroll;
+            }
+        }
+
         let info_widget = Paragraph::new(all_lines)
-            .block(
-                Block::default()
-                    .title(format!("{} Info", item.type_))
-                    .borders(Borders::ALL),
-            )
-            .wrap(ratatui::widgets::Wrap { trim: true });
+            .style(self.theme.text())
+            .block(self.block(if item.type_ == "Episode" {
+                format!("{} · Episode Info", self.season_title(&item))
+            } else {
+                format!("{} Info", item.type_)
+            }))
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .scroll((offset, 0));
 
         frame.render_widget(info_widget, *chunks.last().unwrap());
 
@@ -576,6 +3076,18 @@ impl App {
             return;
         }
 
+        if self.stills.get(&item.id).is_some_and(Option::is_some) {
+            let block = self.block(item.series_name.clone().unwrap_or_default());
+            let area = block.inner(chunks[0]);
+            frame.render_widget(block, chunks[0]);
+
+            if let Some(Some(still)) = self.stills.get_mut(&item.id) {
+                frame.render_stateful_widget(StatefulImage::default(), area, still);
+            }
+
+            return;
+        }
+
         let series_id = match &item.series_id {
             Some(series_id) => series_id,
             None => return,
@@ -590,12 +3102,26 @@ impl App {
             return;
         }
 
-        return self.draw_media_panel(frame, chunks[0], Some(parent.clone()));
+        self.draw_media_panel(frame, chunks[0], Some(parent.clone()));
+    }
+
+    fn block<'a>(&self, title: impl Into<Line<'a>>) -> Block<'a> {
+        Block::default()
+            .title(title)
+            .title_style(Style::default().fg(self.theme.title))
+            .borders(Borders::ALL)
     }
 
     fn draw_search_bar(&self, frame: &mut Frame, chunk: ratatui::prelude::Rect) {
+        let mut block = self.block("Search");
+
+        if let Some((title, _, _)) = &self.detached {
+            block = block.title(Line::from(format!(" Playing: {} ", title)).right_aligned());
+        }
+
         let search_block = Paragraph::new(self.query.as_str())
-            .block(Block::default().title("Search").borders(Borders::ALL));
+            .style(self.theme.text())
+            .block(block);
         frame.render_widget(search_block, chunk);
     }
 
@@ -608,21 +3134,61 @@ impl App {
         let mut lines = Vec::new();
 
         for (index, item) in enumerate(self.selection_options(Some(&state))) {
-            let title = if let Some(year) = item.year {
+            // ? outside of a series' own episode list, episodes need their series to be told apart
+            let title = if item.type_ == "Episode" && state == SelectionState::Main {
+                format!(
+                    "  {} — S{:02}E{:02} — {}",
+                    item.series_name.as_deref().unwrap_or("Unknown Series"),
+                    item.parent_index_number.unwrap_or(0),
+                    item.index_number.unwrap_or(0),
+                    item.name
+                )
+            } else if let Some(year) = item.year {
                 format!("  {} ({})", item.name, year)
             } else {
                 format!("  {}", item.name)
             };
 
+            let title = match item
+                .user_data
+                .as_ref()
+                .and_then(|data| data.unplayed_item_count)
+            {
+                Some(count) if item.type_ == "Series" && count > 0 => {
+                    format!("{} ({} new)", title, count)
+                }
+                _ => title,
+            };
+
+            let title = if self.playing_elsewhere.contains_key(&item.id) {
+                format!("{} ▶", title)
+            } else {
+                title
+            };
+
+            let title = if item.user_data.as_ref().is_some_and(|data| data.is_favorite) {
+                format!("{} ♥", title)
+            } else {
+                title
+            };
+
+            // ? marked items take the indentation for their glyph, like the selection marker
+            let title = if self.selected_ids.contains(&item.id) {
+                format!("● {}", title.trim_start())
+            } else {
+                title
+            };
+
+            // ? within the borders, the selection marker takes the place of the indentation
+            let title = truncate_to_width(&title, chunk.width.saturating_sub(2) as usize);
+
             let span = if index == self.index(Some(&state)) {
                 vec![
-                    Span::styled("> ".to_string(), Style::default().fg(Color::Yellow)),
                     Span::styled(
-                        title.trim_start().to_string(),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
+                        "> ".to_string(),
+                        Style::default().fg(self.theme.selection_highlight),
                     ),
+                    Span::styled(title.trim_start().to_string(), self.theme.selection()),
                 ]
             } else {
                 vec![Span::raw(title.to_string())]
@@ -631,7 +3197,12 @@ impl App {
             lines.push(Line::from(span));
         }
 
-        let visible_height = chunk.height as usize - 2;
+        let visible_height = chunk.height.saturating_sub(2) as usize;
+
+        if lines.is_empty() {
+            lines.extend(vec![Line::default(); visible_height.saturating_sub(1) / 2]);
+            lines.push(Line::from(self.empty_placeholder(&state)).centered());
+        }
 
         let mut selection;
 
@@ -645,6 +3216,7 @@ impl App {
         }
 
         selection.visible_height = visible_height;
+        selection.area = chunk;
 
         if selection.index < selection.scroll_position + 3 {
             selection.scroll_position = selection.index.saturating_sub(3);
@@ -657,35 +3229,15 @@ impl App {
         let title = match state {
             SelectionState::Main => {
                 self.main_selection = selection;
-                let mut categories = if self.query.is_empty() {
-                    vec![
-                        ("Continue Watching", Page::ContinueWatching),
-                        ("Next Up", Page::NextUp),
-                        ("Latest Added", Page::LatestAdded),
-                        ("Movies", Page::AllMovies),
-                        ("Series", Page::AllSeries),
-                    ]
-                } else {
-                    vec![
-                        ("All", Page::All),
-                        ("Movies", Page::Movies),
-                        ("Series", Page::Series),
-                    ]
-                };
-
-                if self.config.include_episodes && !self.query.is_empty() {
-                    categories.push(("Episodes", Page::Episodes));
-                }
-
                 itertools::Itertools::intersperse(
-                    categories.iter().map(|(name, page)| {
+                    self.pages().iter().map(|page| {
                         if *page == self.page {
                             Span::styled(
-                                name.to_string(),
+                                self.page_title(*page),
                                 Style::default().add_modifier(Modifier::BOLD),
                             )
                         } else {
-                            Span::raw(name.to_string())
+                            Span::raw(self.page_title(*page))
                         }
                     }),
                     Span::raw(" "),
@@ -696,12 +3248,23 @@ impl App {
                 self.episode_selection = selection;
 
                 match &self.episode_selection.series {
-                    Some(series) => vec![Span::raw(format!("{} Episodes", series.name))],
+                    Some(series) => vec![Span::raw(format!(
+                        "{} {}",
+                        series.name,
+                        match series.type_.as_str() {
+                            "MusicArtist" => "Albums",
+                            "MusicAlbum" => "Tracks",
+                            "Playlist" => "Playlist",
+                            _ => "Episodes",
+                        }
+                    ))],
                     None => vec![Span::raw("No series selected")],
                 }
             }
         };
 
+        let total = lines.len();
+
         let lines = lines
             .iter()
             .skip(self.scroll_position(Some(&state)))
@@ -709,21 +3272,78 @@ impl App {
             .cloned()
             .collect::<Vec<_>>();
 
-        let widget =
-            Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL));
+        let widget = Paragraph::new(lines)
+            .style(self.theme.text())
+            .block(self.block(title));
 
         frame.render_widget(widget, chunk);
+
+        if total <= visible_height {
+            return;
+        }
+
+        let mut scrollbar_state = ScrollbarState::new(total - visible_height)
+            .position(self.scroll_position(Some(&state)));
+
+        // ? drawn over the right border, between the corners
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            chunk.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+    }
+
+    fn empty_placeholder(&self, state: &SelectionState) -> String {
+        if *state == SelectionState::Episode {
+            if let Action::LoadingEpisodes(_) = self.current_action {
+                return "Loading episodes...".to_string();
+            }
+
+            if self
+                .episode_selection
+                .series
+                .as_ref()
+                .is_some_and(|series| series.type_ == "Series")
+            {
+                return "No episodes found — try refreshing with F5".to_string();
+            }
+
+            return "Nothing here, refresh with F5 if this looks wrong".to_string();
+        }
+
+        if !self.query.is_empty() {
+            return format!("Nothing matches \"{}\"", self.query);
+        }
+
+        match self.page {
+            Page::ContinueWatching | Page::NextUp => {
+                "Nothing here yet — start watching something, or type to search".to_string()
+            }
+            Page::Recommended => {
+                "Nothing to recommend yet — watch something, or refresh with F5 to fetch genres"
+                    .to_string()
+            }
+            _ => "Nothing here yet — type to search, or refresh with F5".to_string(),
+        }
     }
 
     fn draw_action(&mut self, frame: &mut Frame, inner_area: Rect) {
-        let popup_text;
+        let popup_text: Text;
         let title;
+        let mut popup_height = 6;
+        let mut alignment = Alignment::Center;
+        let mut scroll = 0;
 
         match &self.current_action {
-            Action::None => return,
-            Action::NowPlaying(item) => {
+            Action::None | Action::LoadingEpisodes(_) => return,
+            Action::NowPlaying(item, ..) => {
                 title = "Media Playing";
-                popup_text = if item.type_ == "Episode" {
+                let mut text = if item.type_ == "Episode" {
                     format!(
                         "Now Playing:\n\n{}\nS{:02}E{:02} - {}",
                         item.series_name.as_deref().unwrap_or(""),
@@ -734,15 +3354,277 @@ impl App {
                 } else {
                     format!("Now Playing:\n\n{}", item.name)
                 };
+                if !self.jellyfin.config().detached_playback {
+                    text.push_str("\n\nPress Ctrl + x to stop");
+                }
+                popup_text = text.into();
+            }
+            Action::ChoosingSource(item, sources, index, _) => {
+                title = "Choose Version";
+                popup_height = sources.len() as u16 + 4;
+
+                let mut lines = vec![Line::from(item.name.clone()), Line::default()];
+                lines.extend(sources.iter().enumerate().map(|(i, source)| {
+                    if i == *index {
+                        Line::styled(source.label(), self.theme.selection())
+                    } else {
+                        Line::from(source.label())
+                    }
+                }));
+                popup_text = lines.into();
+            }
+            Action::ChoosingPlaylist(items, index) => {
+                title = "Add to Playlist";
+                popup_height = self.jellyfin.playlists.len() as u16 + 4;
+
+                let mut lines = vec![Line::from(describe_items(items)), Line::default()];
+                lines.extend(
+                    self.jellyfin
+                        .playlists
+                        .iter()
+                        .enumerate()
+                        .map(|(i, playlist)| {
+                            if i == *index {
+                                Line::styled(playlist.name.clone(), self.theme.selection())
+                            } else {
+                                Line::from(playlist.name.clone())
+                            }
+                        }),
+                );
+                popup_text = lines.into();
+            }
+            Action::ChoosingLibrary(index) => {
+                title = "Latest Added In";
+                popup_height = self.jellyfin.libraries.len() as u16 + 3;
+
+                let names = std::iter::once("All libraries".to_string()).chain(
+                    self.jellyfin
+                        .libraries
+                        .iter()
+                        .map(|library| library.name.clone()),
+                );
+
+                popup_text = names
+                    .enumerate()
+                    .map(|(i, name)| {
+                        if i == *index {
+                            Line::styled(name, self.theme.selection())
+                        } else {
+                            Line::from(name)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .into();
+            }
+            Action::AddingToPlaylist(items, playlist) => {
+                title = "Adding";
+                popup_text =
+                    format!("\nAdding {}\nto {}", describe_items(items), playlist.name).into();
+            }
+            Action::PlayingQueue(queue) => {
+                title = "Media Playing";
+                let mut text = format!(
+                    "Now Playing:\n\n{}\n{} unwatched episodes",
+                    queue[0].series_name.as_deref().unwrap_or(""),
+                    queue.len()
+                );
+                if !self.jellyfin.config().detached_playback {
+                    text.push_str("\n\nPress Ctrl + x to stop");
+                }
+                popup_text = text.into();
+            }
+            Action::ConfirmingDelete(item) => {
+                title = "Delete";
+                popup_text = format!(
+                    "Delete {} from the server?\nThis cannot be undone.\n\nPress y to delete, any other key to cancel",
+                    item.name
+                )
+                .into();
+            }
+            Action::ConfirmingShuffle(item) => {
+                title = "Shuffle";
+                let name = if item.type_ == "Episode" {
+                    format!(
+                        "{}\nS{:02}E{:02} - {}",
+                        item.series_name.as_deref().unwrap_or(""),
+                        item.parent_index_number.unwrap_or(0),
+                        item.index_number.unwrap_or(0),
+                        item.name
+                    )
+                } else {
+                    item.name.clone()
+                };
+                popup_height = 7;
+                popup_text =
+                    format!("{}\n\nPress Enter to play, any other key to cancel", name).into();
+            }
+            Action::ConfirmingRewatch(item) => {
+                title = "Watched";
+                popup_height = 8;
+                popup_text = format!(
+                    "{}\nwas already watched\n\nPress Enter to play from the start, r to resume,\nany other key to cancel",
+                    item.name
+                )
+                .into();
+            }
+            Action::ReadingOverview(item, offset) => {
+                title = "Overview";
+                alignment = Alignment::Left;
+
+                // ? wrapped here to know how far it can be scrolled, within the popup's borders
+                let width = 60.min(inner_area.width.saturating_sub(4)).saturating_sub(2);
+                let overview = item.overview.as_deref().unwrap_or("No overview available");
+
+                let mut lines = vec![Line::from(item.name.clone()), Line::default()];
+                lines.extend(
+                    textwrap::wrap(overview, width.max(1) as usize)
+                        .into_iter()
+                        .map(|line| Line::from(line.to_string())),
+                );
+
+                popup_height = lines.len() as u16 + 2;
+
+                let visible_height = inner_area.height.saturating_sub(6);
+                scroll = (*offset).min((lines.len() as u16).saturating_sub(visible_height));
+                popup_text = lines.into();
+            }
+            Action::EnteringStart(item, input) => {
+                title = "Start At";
+                popup_height = 7;
+                popup_text = format!(
+                    "{}\n\n{}_\n\nPercentage (50%) or timestamp (1:23:45), Enter to play",
+                    item.name, input
+                )
+                .into();
+            }
+            Action::Deleting(item) => {
+                title = "Deleting";
+                popup_text = format!("\nDeleting {}\nPlease wait...", item.name).into();
+            }
+            Action::MarkingPlayed(batch, played) => {
+                title = if *played {
+                    "Marking Played"
+                } else {
+                    "Marking Unplayed"
+                };
+                popup_text = format!(
+                    "\nMarking as {}\n{}",
+                    if *played { "played" } else { "unplayed" },
+                    batch.progress()
+                )
+                .into();
+            }
+            Action::Favoriting(batch, favorite) => {
+                title = "Favorites";
+                popup_text = format!(
+                    "\n{} favorites\n{}",
+                    if *favorite {
+                        "Adding to"
+                    } else {
+                        "Removing from"
+                    },
+                    batch.progress()
+                )
+                .into();
+            }
+            Action::ConfirmingRefresh => {
+                title = "Refresh";
+                popup_height = 7;
+                popup_text = format!(
+                    "This will re-download all {} items\nand can take a while\n\nPress Enter to refresh, any other key to cancel",
+                    format_count(self.jellyfin.items.len())
+                )
+                .into();
+            }
+            Action::CopyingUrl(item) => {
+                title = "Copying";
+                popup_text = format!("\nGetting the stream URL of\n{}", item.name).into();
+            }
+            Action::Copied(item) => {
+                title = "Copied";
+                popup_text = format!(
+                    "\nCopied the stream URL of\n{}\n\nPress any key to continue",
+                    item.name
+                )
+                .into();
+                popup_height = 7;
+            }
+            Action::RefreshingItem(item) => {
+                title = "Refreshing";
+                popup_text = format!("\nRefreshing {}\nPlease wait...", item.name).into();
             }
             Action::RefreshingCache => {
                 title = "Refreshing";
-                popup_text = "\nRefreshing cache and home page\nPlease wait...".to_string();
+                popup_text = "\nRefreshing cache and home page\nPlease wait...".into();
+            }
+            Action::RefreshingHome => {
+                title = "Refreshing";
+                popup_text = "\nRefreshing home page\nPlease wait...".into();
+            }
+            Action::RefreshComplete(summary) => {
+                title = "Refreshed";
+                let failed = if summary.failed.is_empty() {
+                    String::new()
+                } else {
+                    format!("\nFailed to fetch {}", summary.failed.join(", "))
+                };
+                popup_text = format!(
+                    "Loaded {} items (+{} new)\nUpdated at {}{}\n\nPress any key to continue",
+                    format_count(summary.total),
+                    format_count(summary.new),
+                    summary
+                        .updated_at
+                        .format(self.jellyfin.config().time_format.pattern()),
+                    failed
+                )
+                .into();
+            }
+            Action::ShowingStats(stats) => {
+                title = "Library";
+                popup_height = 11;
+                popup_text = format!(
+                    "Movies: {} ({} watched)\nSeries: {}\nEpisodes: {} ({} watched)\n\nTotal runtime: {}\n{}% of it watched\n\nPress any key to continue",
+                    format_count(stats.movies),
+                    format_count(stats.watched_movies),
+                    format_count(stats.series),
+                    format_count(stats.episodes),
+                    format_count(stats.watched_episodes),
+                    format_ticks(stats.runtime_ticks),
+                    stats.watched_percent()
+                )
+                .into();
+            }
+            Action::ShowingMenu(item, index) => {
+                title = "Actions";
+
+                let entries = self.menu_entries(item);
+                popup_height = entries.len() as u16 + 4;
+
+                let mut lines = vec![Line::from(item.name.clone()), Line::default()];
+                lines.extend(entries.iter().enumerate().map(|(i, entry)| {
+                    let label = if entry.hint.is_empty() {
+                        entry.label.clone()
+                    } else {
+                        format!("{} ({})", entry.label, entry.hint)
+                    };
+
+                    if i == *index {
+                        Line::styled(label, self.theme.selection())
+                    } else {
+                        Line::from(label)
+                    }
+                }));
+                popup_text = lines.into();
+            }
+            Action::Error(message) => {
+                title = "Error";
+                popup_text = format!("\n{}\n\nPress any key to continue", message).into();
+                popup_height = message.lines().count() as u16 + 5;
             }
         }
 
-        let popup_width = 60.min(inner_area.width - 4);
-        let popup_height = 6.min(inner_area.height - 4);
+        let popup_width = 60.min(inner_area.width.saturating_sub(4));
+        let popup_height = popup_height.min(inner_area.height.saturating_sub(4));
 
         let popup_area = Layout::default()
             .direction(Direction::Horizontal)
@@ -763,16 +3645,477 @@ impl App {
             );
 
         let popup = Paragraph::new(popup_text)
+            .style(self.theme.text())
             .block(
-                Block::default()
-                    .title(title)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Red)),
+                self.block(title)
+                    .border_type(self.theme.popup_border_type())
+                    .border_style(Style::default().fg(self.theme.popup_border)),
             )
-            .alignment(Alignment::Center)
-            .wrap(ratatui::widgets::Wrap { trim: true });
+            .alignment(alignment)
+            .wrap(ratatui::widgets::Wrap { trim: true })
+            .scroll((scroll, 0));
+
+        // ? clamped where the popup's size is known, so scrolling back up responds right away
+        if let Action::ReadingOverview(_, offset) = &mut self.current_action {
+            *offset = scroll;
+        }
 
         frame.render_widget(Clear, popup_area[1]);
         frame.render_widget(popup, popup_area[1]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jellyfin::UserData;
+
+    fn item(name: &str) -> MediaItem {
+        MediaItem {
+            id: name.to_string(),
+            name: name.to_string(),
+            type_: "Movie".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn names(items: Vec<MediaItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.name).collect()
+    }
+
+    #[test]
+    fn fuzzy_filter_empty_query_returns_all() {
+        let pool = vec![item("Alien"), item("Brazil"), item("Heat")];
+
+        assert_eq!(
+            names(search_filter(&pool, "", SearchMatcher::Fuzzy, None, |_| 0)),
+            vec!["Alien", "Brazil", "Heat"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_filter_is_case_insensitive() {
+        let pool = vec![item("The Matrix"), item("Heat")];
+
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "MATRIX",
+                SearchMatcher::Fuzzy,
+                None,
+                |_| 0
+            )),
+            vec!["The Matrix"]
+        );
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "matrix",
+                SearchMatcher::Fuzzy,
+                None,
+                |_| 0
+            )),
+            vec!["The Matrix"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_filter_ranks_better_matches_first() {
+        let pool = vec![item("Mad Max: Fury Road"), item("Alien"), item("Max")];
+
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "max",
+                SearchMatcher::Fuzzy,
+                None,
+                |_| 0
+            )),
+            vec!["Max", "Mad Max: Fury Road"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_filter_weighted_prefers_weighted_items() {
+        let pool = vec![
+            item("Lost"),
+            MediaItem {
+                id: "preferred".to_string(),
+                ..item("Lost")
+            },
+        ];
+
+        let filtered = search_filter(&pool, "lost", SearchMatcher::Fuzzy, None, |item| {
+            if item.id == "preferred" {
+                10
+            } else {
+                0
+            }
+        });
+
+        assert_eq!(filtered[0].id, "preferred");
+    }
+
+    #[test]
+    fn search_filter_matchers_and_threshold() {
+        let pool = vec![item("The Matrix"), item("Matrimony"), item("Mad Max")];
+
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "mtrx",
+                SearchMatcher::Fuzzy,
+                None,
+                |_| 0
+            )),
+            vec!["The Matrix"]
+        );
+        assert!(search_filter(&pool, "mtrx", SearchMatcher::Substring, None, |_| 0).is_empty());
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "trix",
+                SearchMatcher::Substring,
+                None,
+                |_| 0
+            )),
+            vec!["The Matrix"]
+        );
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "ma",
+                SearchMatcher::Prefix,
+                None,
+                |_| 0
+            )),
+            vec!["Mad Max", "Matrimony", "The Matrix"]
+        );
+        assert!(search_filter(&pool, "mtrx", SearchMatcher::Fuzzy, Some(1000), |_| 0).is_empty());
+        assert_eq!(
+            names(search_filter(
+                &pool,
+                "trix",
+                SearchMatcher::Substring,
+                Some(1000),
+                |_| 0
+            )),
+            vec!["The Matrix"]
+        );
+    }
+
+    #[test]
+    fn parse_query_separates_filters_from_text() {
+        let (text, filters) = parse_query("matrix year:1999 rating:>7 genre:scifi");
+
+        assert_eq!(text, "matrix genre:scifi");
+        assert_eq!(
+            filters,
+            [
+                QueryFilter {
+                    field: FilterField::Year,
+                    comparison: Comparison::Equal,
+                    value: 1999.0,
+                },
+                QueryFilter {
+                    field: FilterField::Rating,
+                    comparison: Comparison::Greater,
+                    value: 7.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_query_keeps_invalid_filters_as_text() {
+        let (text, filters) = parse_query("year:nineties rating:");
+
+        assert_eq!(text, "year:nineties rating:");
+        assert!(filters.is_empty());
+    }
+
+    #[test]
+    fn query_filters_match_ranges() {
+        let (_, filters) = parse_query("year:1980-1989");
+
+        let mut item = item("Blade Runner");
+        item.year = Some(1982);
+        assert!(filters.iter().all(|filter| filter.matches(&item)));
+
+        item.year = Some(1990);
+        assert!(!filters.iter().all(|filter| filter.matches(&item)));
+
+        item.year = None;
+        assert!(!filters.iter().all(|filter| filter.matches(&item)));
+    }
+
+    #[test]
+    fn next_unwatched_skips_watched_episodes() {
+        let mut watched = item("watched");
+        watched.user_data = Some(UserData {
+            played: true,
+            ..Default::default()
+        });
+
+        let episodes = vec![watched.clone(), item("next"), item("later")];
+        assert_eq!(
+            next_unwatched(&episodes).map(|ep| ep.name),
+            Some("next".to_string())
+        );
+
+        let episodes = vec![watched.clone(), watched];
+        assert_eq!(
+            next_unwatched(&episodes).map(|ep| ep.name),
+            Some("watched".to_string())
+        );
+
+        assert!(next_unwatched(&[]).is_none());
+    }
+
+    #[test]
+    fn shuffle_pick_prefers_unwatched_episodes() {
+        let mut watched = item("Watched");
+        watched.user_data = Some(UserData {
+            played: true,
+            ..Default::default()
+        });
+        let episodes = [watched.clone(), item("Unwatched"), watched];
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let pick = shuffle_pick(&episodes, &mut rng).map(|item| item.name);
+            assert_eq!(pick.as_deref(), Some("Unwatched"));
+        }
+    }
+
+    #[test]
+    fn shuffle_pick_falls_back_to_watched_episodes() {
+        let mut watched = item("Watched");
+        watched.user_data = Some(UserData {
+            played: true,
+            ..Default::default()
+        });
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let pick = shuffle_pick(&[watched], &mut rng).map(|item| item.name);
+
+        assert_eq!(pick.as_deref(), Some("Watched"));
+        assert_eq!(shuffle_pick(&[], &mut rng).map(|item| item.name), None);
+    }
+
+    #[test]
+    fn sort_by_last_watched_puts_recent_series_first() {
+        let series = |id: &str, last_played: Option<&str>| {
+            let mut series = item(id);
+            series.user_data = Some(UserData {
+                last_played_date: last_played.map(str::to_string),
+                ..Default::default()
+            });
+            (id.to_string(), series)
+        };
+        let episode = |series_id: &str| {
+            let mut episode = item(&format!("{} episode", series_id));
+            episode.series_id = Some(series_id.to_string());
+            episode
+        };
+
+        let items = HashMap::from([
+            series("never", None),
+            series("old", Some("2024-01-01T20:00:00.0000000Z")),
+            series("recent", Some("2024-03-01T20:00:00.0000000Z")),
+        ]);
+
+        let sorted = sort_by_last_watched(
+            &[episode("never"), episode("old"), episode("recent")],
+            &items,
+        );
+
+        assert_eq!(
+            names(sorted),
+            ["recent episode", "old episode", "never episode"]
+        );
+    }
+
+    #[test]
+    fn parse_start_accepts_percentages_and_timestamps() {
+        let runtime = Some(100 * 60 * 10_000_000);
+
+        assert_eq!(parse_start("50%", runtime), Some(50 * 60 * 10_000_000));
+        assert_eq!(parse_start("25", runtime), Some(25 * 60 * 10_000_000));
+        assert_eq!(parse_start("1:02:03", runtime), Some(3723 * 10_000_000));
+        assert_eq!(parse_start("12:30", None), Some(750 * 10_000_000));
+        assert_eq!(parse_start("50%", None), None);
+        assert_eq!(parse_start("150%", runtime), None);
+        assert_eq!(parse_start("1:xx", runtime), None);
+    }
+
+    #[test]
+    fn page_from_config_only_accepts_home_pages() {
+        assert_eq!(Page::from_config("next_up"), Some(Page::NextUp));
+        assert_eq!(Page::from_config("movies"), Some(Page::AllMovies));
+        assert_eq!(Page::from_config("recommended"), Some(Page::Recommended));
+        assert_eq!(Page::from_config("search"), None);
+        assert_eq!(Page::from_config("NextUp"), None);
+    }
+
+    #[test]
+    fn recommendations_share_genres_with_the_last_watched() {
+        let title = |name: &str, genres: &[&str], last_played: Option<&str>, played: bool| {
+            let mut item = item(name);
+            item.genres = genres.iter().map(|genre| genre.to_string()).collect();
+            item.user_data = Some(UserData {
+                played,
+                last_played_date: last_played.map(str::to_string),
+                ..Default::default()
+            });
+            (item.id.clone(), item)
+        };
+
+        let items = HashMap::from([
+            title(
+                "Alien",
+                &["Horror", "Science Fiction"],
+                Some("2024-01-01"),
+                true,
+            ),
+            title(
+                "Arrival",
+                &["Drama", "Science Fiction"],
+                Some("2024-03-01"),
+                true,
+            ),
+            title("Contact", &["Drama", "Science Fiction"], None, false),
+            title("Interstellar", &["Science Fiction"], None, false),
+            title("Moon", &["Science Fiction"], None, true),
+            title("Heat", &["Crime"], None, false),
+        ]);
+
+        let (watched, recommended) = recommendations(&items).unwrap();
+
+        assert_eq!(watched.name, "Arrival");
+        assert_eq!(names(recommended), ["Contact", "Interstellar"]);
+    }
+
+    #[test]
+    fn unwatched_mixes_movies_with_next_episodes() {
+        let entry = |mut item: MediaItem, added: &str, rating: f32, played: bool| {
+            item.date_created = Some(added.to_string());
+            item.imdb_rating = Some(rating);
+            item.user_data = Some(UserData {
+                played,
+                ..Default::default()
+            });
+            (item.id.clone(), item)
+        };
+        let episode = |name: &str, series: &str, index: i64| MediaItem {
+            type_: "Episode".to_string(),
+            series_id: Some(series.to_string()),
+            parent_index_number: Some(1),
+            index_number: Some(index),
+            ..item(name)
+        };
+
+        let items = HashMap::from([
+            entry(item("Alien"), "2024-01-01", 8.5, false),
+            entry(item("Heat"), "2024-03-01", 8.3, false),
+            entry(item("Moon"), "2024-04-01", 7.8, true),
+            // started, so its next episode is in
+            entry(episode("Pilot", "lost", 1), "2024-02-01", 9.0, true),
+            entry(episode("Tabula Rasa", "lost", 2), "2024-02-01", 9.0, false),
+            // not started
+            entry(episode("Genesis", "heroes", 1), "2024-05-01", 9.5, false),
+            // watched to the end
+            entry(episode("Finale", "done", 1), "2024-05-01", 9.5, true),
+        ]);
+
+        assert_eq!(
+            names(unwatched(&items, UnwatchedSort::DateCreated)),
+            ["Heat", "Tabula Rasa", "Alien"]
+        );
+        assert_eq!(
+            names(unwatched(&items, UnwatchedSort::Rating)),
+            ["Tabula Rasa", "Alien", "Heat"]
+        );
+    }
+
+    #[test]
+    fn recommendations_need_something_watched() {
+        assert!(recommendations(&HashMap::from([("Alien".to_string(), item("Alien"))])).is_none());
+    }
+
+    #[test]
+    fn enabled_pages_keeps_the_configured_order() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            enabled_pages(&names(&["series", "continue_watching", "bogus", "series"])),
+            vec![Page::AllSeries, Page::ContinueWatching]
+        );
+        assert_eq!(enabled_pages(&names(&["bogus"])), HOME_PAGES.to_vec());
+        assert_eq!(enabled_pages(&[]), HOME_PAGES.to_vec());
+    }
+
+    #[test]
+    fn describe_items_names_single_items() {
+        assert_eq!(describe_items(&[item("Alien")]), "Alien");
+        assert_eq!(describe_items(&[item("Alien"), item("Aliens")]), "2 items");
+    }
+
+    #[test]
+    fn truncate_to_width_counts_wide_characters() {
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_to_width("a long title", 8), "a long …");
+        assert_eq!(truncate_to_width("千と千尋の神隠し", 7), "千と千…");
+        assert_eq!(truncate_to_width("anything", 0), "…");
+    }
+
+    #[test]
+    fn batch_only_keeps_successful_changes() {
+        let batch = Batch::new(vec![item("Alien"), item("Brazil"), item("Heat")]);
+        assert_eq!(batch.progress(), "1 of 3");
+
+        let batch = batch.advance().unwrap();
+        assert_eq!(batch.progress(), "2 of 3");
+        assert_eq!(names(batch.finish(false).unwrap()), vec!["Alien"]);
+
+        let batch = batch.advance().unwrap();
+        assert!(batch.advance().is_none());
+        assert_eq!(
+            names(batch.finish(true).unwrap()),
+            vec!["Alien", "Brazil", "Heat"]
+        );
+
+        assert!(Batch::new(vec![item("Alien")]).finish(false).is_none());
+        assert!(Batch::undo(vec![item("Alien")]).finish(true).is_none());
+    }
+
+    #[test]
+    fn remaining_summary_counts_episodes() {
+        let hour = 60 * 60 * 10_000_000;
+
+        assert_eq!(remaining_summary(0, 0), "All episodes watched");
+        assert_eq!(remaining_summary(1, hour / 2), "1 episode left · ~30m");
+        assert_eq!(remaining_summary(4, 3 * hour), "4 episodes left · ~3h 0m");
+    }
+
+    #[test]
+    fn format_count_groups_thousands() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(4213), "4,213");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn fuzzy_filter_no_match() {
+        let pool = vec![item("Alien"), item("Heat")];
+
+        assert!(search_filter(&pool, "zzz", SearchMatcher::Fuzzy, None, |_| 0).is_empty());
+    }
+}
@@ -5,33 +5,348 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use directories::BaseDirs;
+use rand::Rng;
+use reqwest::{Certificate, Client, Identity};
 use rpassword::read_password;
 use serde::{Deserialize, Serialize};
 use toml::{from_str, to_string};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
+    #[serde(default)]
+    pub server_type: ServerType,
     #[serde(default)]
     pub accept_self_signed: bool,
+    #[serde(default)]
+    pub ca_cert_path: Option<PathBuf>,
+    #[serde(default)]
+    pub client_cert_path: Option<PathBuf>,
+    #[serde(default)]
+    pub client_key_path: Option<PathBuf>,
     pub server_url: String,
     pub username: String,
     pub password: String,
     #[serde(default)]
+    pub player: Option<String>,
+    #[serde(default)]
+    pub mpv_args: Vec<String>,
+    #[serde(default)]
+    pub vim_keys: bool,
+    #[serde(default)]
+    pub skip_intros: bool,
+    #[serde(default)]
+    pub search_weights: SearchWeights,
+    #[serde(default)]
+    pub search_matcher: SearchMatcher,
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    #[serde(default)]
+    pub min_search_score: Option<i64>,
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    #[serde(default)]
+    pub no_color: bool,
+    #[serde(default)]
+    pub trakt: Option<TraktConfig>,
+    #[serde(default)]
+    pub subtitles: Option<SubtitlesConfig>,
+    #[serde(default)]
+    pub allow_delete: bool,
+    #[serde(default)]
+    pub forced_subtitles: bool,
+    #[serde(default)]
+    pub subtitle_visibility: Option<bool>,
+    #[serde(default)]
+    pub audio_languages: Vec<String>,
+    #[serde(default)]
+    pub subtitle_languages: Vec<String>,
+    #[serde(default)]
+    pub include_episodes: bool,
+    #[serde(default)]
+    pub next_up_by_last_watched: bool,
+    #[serde(default)]
+    pub episodes_newest_first: bool,
+    #[serde(default)]
+    pub detached_playback: bool,
+    #[serde(default)]
+    pub latest_added_sort: LatestAddedSort,
+    #[serde(default)]
+    pub cache_format: CacheFormat,
+    #[serde(default)]
+    pub unwatched_sort: UnwatchedSort,
+    #[serde(default)]
+    pub watched_threshold_percent: Option<u8>,
+    #[serde(default)]
+    pub mark_watched: MarkWatched,
+    #[serde(default)]
+    pub rewatch: Rewatch,
+    #[serde(default)]
+    pub time_format: TimeFormat,
+    #[serde(default)]
+    pub no_altscreen: bool,
+    #[serde(default)]
+    pub max_overview_lines: Option<usize>,
+    #[serde(default)]
+    pub max_streaming_bitrate: Option<u64>,
+    #[serde(default)]
+    pub progress_report_interval_secs: Option<u64>,
+    #[serde(default)]
+    pub tick_rate_ms: Option<u64>,
+    #[serde(default)]
+    pub confirm_refresh: bool,
+    #[serde(default)]
+    pub startup_page: Option<String>,
+    #[serde(default)]
+    pub enabled_pages: Vec<String>,
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    // ? kept in the data dir instead of config.toml, which may be read-only or managed elsewhere
+    #[serde(skip)]
+    generated_device_id: Option<String>,
+    #[serde(default, skip_serializing)]
     pub is_new: bool,
 }
 
-impl Default for Config {
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SearchWeights {
+    // added to the fuzzy score of movies and series, so they rank above similarly named episodes
+    pub type_boost: i64,
+    // added to the fuzzy score of items in continue watching or next up
+    pub recent_boost: i64,
+}
+
+impl Default for SearchWeights {
     fn default() -> Self {
         Self {
-            accept_self_signed: false,
-            server_url: String::new(),
-            username: String::new(),
-            password: String::new(),
-            is_new: false,
+            type_boost: 10,
+            recent_boost: 20,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LayoutConfig {
+    // width of the info panel on the left, in percent of the window
+    #[serde(default)]
+    pub info_panel_percent: Option<u16>,
+    // drops the info panel, so the lists take up the whole width
+    #[serde(default)]
+    pub compact: bool,
+}
+
+impl LayoutConfig {
+    // ? both sides always keep some room, compact mode is the way to drop the info panel
+    pub fn info_panel_percent(&self) -> u16 {
+        if self.compact {
+            0
+        } else {
+            self.info_panel_percent.unwrap_or(30).clamp(15, 80)
         }
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMatcher {
+    // characters in order, with gaps, e.g. "mtrx" finds "The Matrix"
+    #[default]
+    Fuzzy,
+    // the query as is, anywhere in the name
+    Substring,
+    // the start of any word in the name
+    Prefix,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub selection_fg: Option<String>,
+    #[serde(default)]
+    pub selection_highlight: Option<String>,
+    #[serde(default)]
+    pub popup_border: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub normal_text: Option<String>,
+}
+
+// ? emby shares most of the api, only the paths and headers that differ are switched
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerType {
+    #[default]
+    Jellyfin,
+    Emby,
+}
+
+impl ServerType {
+    pub fn token_header(&self) -> &'static str {
+        match self {
+            ServerType::Jellyfin => "X-MediaBrowser-Token",
+            ServerType::Emby => "X-Emby-Token",
+        }
+    }
+
+    pub fn websocket_path(&self) -> &'static str {
+        match self {
+            ServerType::Jellyfin => "/socket",
+            ServerType::Emby => "/embywebsocket",
+        }
+    }
+
+    pub fn details_path(&self) -> &'static str {
+        match self {
+            ServerType::Jellyfin => "/web/index.html#!/details?id=",
+            ServerType::Emby => "/web/index.html#!/item?id=",
+        }
+    }
+
+    // ? jellyfin 10.9 moved this off the user, emby never did
+    pub fn played_item_path(&self, user_id: &str, item_id: &str) -> String {
+        match self {
+            ServerType::Jellyfin => format!("/UserPlayedItems/{}", item_id),
+            ServerType::Emby => format!("/Users/{}/PlayedItems/{}", user_id, item_id),
+        }
+    }
+
+    pub fn favorite_item_path(&self, user_id: &str, item_id: &str) -> String {
+        match self {
+            ServerType::Jellyfin => format!("/UserFavoriteItems/{}", item_id),
+            ServerType::Emby => format!("/Users/{}/FavoriteItems/{}", user_id, item_id),
+        }
+    }
+
+    pub fn user_data_path(&self, user_id: &str, item_id: &str) -> String {
+        match self {
+            ServerType::Jellyfin => format!("/UserItems/{}/UserData", item_id),
+            ServerType::Emby => format!("/Users/{}/Items/{}/UserData", user_id, item_id),
+        }
+    }
+
+    // ? emby has no /Users/Me, there the user id from the last login is needed
+    pub fn current_user_path(&self, user_id: Option<&str>) -> Option<String> {
+        match self {
+            ServerType::Jellyfin => Some("/Users/Me".to_string()),
+            ServerType::Emby => user_id.map(|id| format!("/Users/{}", id)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LatestAddedSort {
+    // when the item was added to the server
+    #[default]
+    DateCreated,
+    // when the item was released, for libraries that were imported all at once
+    PremiereDate,
+}
+
+impl LatestAddedSort {
+    pub fn sort_by(&self) -> &'static str {
+        match self {
+            LatestAddedSort::DateCreated => "DateCreated,SortName",
+            LatestAddedSort::PremiereDate => "PremiereDate,SortName",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheFormat {
+    // readable, and easy to inspect when something looks off
+    #[default]
+    Json,
+    // binary, much quicker to load for large libraries
+    Messagepack,
+}
+
+impl CacheFormat {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CacheFormat::Json => "cache.json",
+            CacheFormat::Messagepack => "cache.msgpack",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnwatchedSort {
+    // most recently added to the server first
+    #[default]
+    DateCreated,
+    // highest community rating first
+    Rating,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rewatch {
+    // from the position the server has, like anything else
+    #[default]
+    Resume,
+    // always from the start
+    Beginning,
+    // asks which of the two
+    Ask,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkWatched {
+    // when stopping past watched_threshold_percent
+    #[default]
+    Threshold,
+    // as soon as playback starts
+    Start,
+    // only when playing to the very end
+    Finish,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum TimeFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+    #[serde(rename = "12h")]
+    TwelveHour,
+}
+
+impl TimeFormat {
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:%M",
+            TimeFormat::TwelveHour => "%I:%M %p",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TraktConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub access_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubtitlesConfig {
+    pub opensubtitles_api_key: String,
+    #[serde(default)]
+    pub languages: Option<String>,
+}
+
+impl SubtitlesConfig {
+    // ? comma separated two letter codes, in opensubtitles' own format
+    pub fn languages(&self) -> &str {
+        self.languages.as_deref().unwrap_or("en")
+    }
+}
+
 impl Config {
     pub fn config_path(base_path: Option<&Path>) -> Option<PathBuf> {
         base_path.map(|p| p.join("config.toml")).or(BaseDirs::new()
@@ -44,14 +359,13 @@ impl Config {
 
         if !config_path.exists() {
             let config = Self::create_initial_config()?;
-            let toml = to_string(&config)?.replace("\nis_new = true", "");
             std::fs::create_dir_all(config_path.parent().unwrap())?;
-            std::fs::write(&config_path, toml)?;
+            std::fs::write(&config_path, to_string(&config)?)?;
 
             return Ok(config);
         }
 
-        let mut contents = std::fs::read_to_string(config_path)?;
+        let mut contents = std::fs::read_to_string(&config_path)?;
         contents.push_str("\nis_new = false");
         let config: Config = from_str(&contents)?;
 
@@ -69,6 +383,159 @@ impl Config {
         Ok(())
     }
 
+    pub fn endpoint(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.server_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
+    // ? the player has to understand mpv's arguments and IPC, so this is mostly for custom mpv builds
+    pub fn player(&self) -> &str {
+        self.player.as_deref().unwrap_or("mpv")
+    }
+
+    pub fn watched_threshold_percent(&self) -> u8 {
+        self.watched_threshold_percent.unwrap_or(90)
+    }
+
+    // ? how far into an item stopping marks it as watched, none when it was marked on start
+    pub fn stop_watched_percent(&self) -> Option<u8> {
+        match self.mark_watched {
+            MarkWatched::Threshold => Some(self.watched_threshold_percent()),
+            MarkWatched::Start => None,
+            MarkWatched::Finish => Some(100),
+        }
+    }
+
+    pub fn device_name(&self) -> String {
+        self.device_name.clone().unwrap_or_else(|| {
+            hostname::get()
+                .map(|h| h.to_string_lossy().to_string())
+                .unwrap_or_else(|_| "unknown-device".to_string())
+        })
+    }
+
+    // ? every install is its own device on the server, "tui" is what all of them used to share
+    pub fn device_id(&self) -> &str {
+        self.device_id
+            .as_deref()
+            .or(self.generated_device_id.as_deref())
+            .unwrap_or("tui")
+    }
+
+    // ? an id set in config.toml wins, otherwise one is made up once and reused from the data dir
+    pub(crate) fn load_device_id(&mut self, data_dir: &Path) {
+        if self.device_id.is_some() {
+            return;
+        }
+
+        let path = data_dir.join("device_id");
+        let stored = std::fs::read_to_string(&path)
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+
+        self.generated_device_id = Some(stored.unwrap_or_else(|| {
+            let device_id = new_device_id();
+            if let Err(e) = std::fs::write(&path, &device_id) {
+                eprintln!("Failed to save device id: {}", e);
+            }
+            device_id
+        }));
+    }
+
+    pub fn progress_report_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.progress_report_interval_secs.unwrap_or(10).max(1))
+    }
+
+    pub fn tick_rate(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.tick_rate_ms.unwrap_or(250).max(16))
+    }
+
+    pub fn player_installed(&self) -> bool {
+        let player = Path::new(self.player());
+
+        if player.components().count() > 1 {
+            return player.is_file();
+        }
+
+        std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join(player).is_file())
+        })
+    }
+
+    // ? in order of preference, as mpv's --alang takes them, none when the server's preference is used
+    pub fn audio_languages(&self) -> Option<String> {
+        language_list(&self.audio_languages)
+    }
+
+    pub fn subtitle_languages(&self) -> Option<String> {
+        language_list(&self.subtitle_languages)
+    }
+
+    // ? playback is monitored through the ipc socket, so arguments that would replace it are left out
+    pub fn mpv_args(&self) -> impl Iterator<Item = &String> {
+        self.mpv_args.iter().filter(|arg| !is_reserved_mpv_arg(arg))
+    }
+
+    pub fn reserved_mpv_args(&self) -> impl Iterator<Item = &String> {
+        self.mpv_args.iter().filter(|arg| is_reserved_mpv_arg(arg))
+    }
+
+    // ? also honors the NO_COLOR convention, see https://no-color.org
+    pub fn no_color(&self) -> bool {
+        self.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+
+    pub fn client(&self) -> Result<Client> {
+        let mut client = Client::builder();
+
+        // prefer a pinned certificate over disabling validation altogether
+        if let Some(ca_cert_path) = &self.ca_cert_path {
+            let pem = read_pem(ca_cert_path, "CA certificate")?;
+            client = client.add_root_certificate(Certificate::from_pem(&pem)?);
+        } else {
+            client = client.danger_accept_invalid_certs(self.accept_self_signed);
+        }
+
+        // ? the key may also be in the certificate file, rustls wants both in one pem
+        if let Some(client_cert_path) = &self.client_cert_path {
+            let mut pem = read_pem(client_cert_path, "client certificate")?;
+            if let Some(client_key_path) = &self.client_key_path {
+                pem.push(b'\n');
+                pem.extend(read_pem(client_key_path, "client key")?);
+            }
+            client = client.identity(Identity::from_pem(&pem)?);
+        }
+
+        Ok(client.build()?)
+    }
+
+    // ? load stays synchronous, and a runtime can't be blocked on from inside another, so the check gets its own thread
+    fn check_server_url(&self) -> Result<()> {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?
+                        .block_on(async {
+                            self.client()?
+                                .get(self.endpoint("/System/Info/Public"))
+                                .send()
+                                .await?
+                                .error_for_status()?;
+
+                            Ok(())
+                        })
+                })
+                .join()
+                .map_err(|_| anyhow::anyhow!("Server check panicked"))?
+        })
+    }
+
     fn create_initial_config() -> Result<Self> {
         print!("\x1B[2J\x1B[1;1H");
         println!("Config file not found");
@@ -79,12 +546,41 @@ impl Config {
         io::stdin().read_line(&mut accept_self_signed)?;
         let accept_self_signed = accept_self_signed.trim().to_string().to_lowercase() == "y";
 
-        print!("Please enter the URL of your Jellyfin server. Example: http://foobar.baz:8096/jf\n\
-               (note: unless specified, ports will be the protocol's defaults, i.e. 80 for HTTP and 443 for HTTPS)\n> ");
-        io::stdout().flush()?;
-        let mut server_url = String::new();
-        io::stdin().read_line(&mut server_url)?;
-        let server_url = server_url.trim().to_string();
+        let mut ca_cert_path = None;
+        if accept_self_signed {
+            print!("Please enter the path to the PEM certificate of your CA, or leave empty to accept any certificate\n> ");
+            io::stdout().flush()?;
+            let mut path = String::new();
+            io::stdin().read_line(&mut path)?;
+            let path = path.trim();
+            if !path.is_empty() {
+                ca_cert_path = Some(PathBuf::from(path));
+            }
+        }
+
+        let mut config = Config {
+            // ? a pinned certificate keeps validation on, so there's no need to accept everything
+            accept_self_signed: accept_self_signed && ca_cert_path.is_none(),
+            ca_cert_path,
+            is_new: true,
+            ..Default::default()
+        };
+
+        loop {
+            print!("Please enter the URL of your Jellyfin server. Example: http://foobar.baz:8096/jf\n\
+                   (note: unless specified, ports will be the protocol's defaults, i.e. 80 for HTTP and 443 for HTTPS)\n> ");
+            io::stdout().flush()?;
+            let mut server_url = String::new();
+            if io::stdin().read_line(&mut server_url)? == 0 {
+                return Err(anyhow::anyhow!("No server URL entered"));
+            }
+            config.server_url = normalize_server_url(&server_url);
+
+            match config.check_server_url() {
+                Ok(_) => break,
+                Err(e) => println!("Could not reach {}: {}\n", config.server_url, e),
+            }
+        }
 
         print!("Please enter your username\n> ");
         io::stdout().flush()?;
@@ -100,11 +596,186 @@ impl Config {
         io::stdout().flush()?;
 
         Ok(Config {
-            accept_self_signed,
-            server_url,
             username,
             password,
-            is_new: true,
+            ..config
         })
     }
 }
+
+fn normalize_server_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+
+    if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("http://{}", url)
+    }
+}
+
+fn read_pem(path: &Path, name: &str) -> Result<Vec<u8>> {
+    std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {} {}: {}", name, path.display(), e))
+}
+
+fn is_reserved_mpv_arg(arg: &str) -> bool {
+    let name = arg.trim_start_matches('-');
+    let name = name.split_once('=').map_or(name, |(name, _)| name);
+
+    matches!(
+        name,
+        "input-ipc-server" | "input-ipc-client" | "no-input-ipc-server"
+    )
+}
+
+fn new_device_id() -> String {
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+fn language_list(languages: &[String]) -> Option<String> {
+    let languages = languages
+        .iter()
+        .map(|language| language.trim())
+        .filter(|language| !language.is_empty())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    (!languages.is_empty()).then_some(languages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mpv_args_skip_the_ipc_socket() {
+        let config = Config {
+            mpv_args: vec![
+                "--hwdec=auto".to_string(),
+                "--input-ipc-server=/tmp/other".to_string(),
+                "--profile=gpu-hq".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.mpv_args().collect::<Vec<_>>(),
+            ["--hwdec=auto", "--profile=gpu-hq"]
+        );
+        assert_eq!(
+            config.reserved_mpv_args().collect::<Vec<_>>(),
+            ["--input-ipc-server=/tmp/other"]
+        );
+    }
+
+    #[test]
+    fn language_lists_are_joined_in_order() {
+        let config = Config {
+            audio_languages: vec!["jpn".to_string(), " eng ".to_string(), "".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(config.audio_languages().as_deref(), Some("jpn,eng"));
+        assert_eq!(config.subtitle_languages(), None);
+    }
+
+    #[test]
+    fn stop_watched_percent_follows_the_policy() {
+        let mut config = Config {
+            watched_threshold_percent: Some(80),
+            ..Default::default()
+        };
+        assert_eq!(config.stop_watched_percent(), Some(80));
+
+        config.mark_watched = MarkWatched::Finish;
+        assert_eq!(config.stop_watched_percent(), Some(100));
+
+        config.mark_watched = MarkWatched::Start;
+        assert_eq!(config.stop_watched_percent(), None);
+    }
+
+    #[test]
+    fn info_panel_percent_keeps_room_for_the_lists() {
+        let mut layout = LayoutConfig::default();
+        assert_eq!(layout.info_panel_percent(), 30);
+
+        layout.info_panel_percent = Some(95);
+        assert_eq!(layout.info_panel_percent(), 80);
+
+        layout.info_panel_percent = Some(0);
+        assert_eq!(layout.info_panel_percent(), 15);
+
+        layout.compact = true;
+        assert_eq!(layout.info_panel_percent(), 0);
+    }
+
+    #[test]
+    fn time_format_parses_clock_names() {
+        let config: Config = from_str(
+            "server_url = \"http://localhost\"\nusername = \"\"\npassword = \"\"\ntime_format = \"12h\"",
+        )
+        .unwrap();
+        assert_eq!(config.time_format, TimeFormat::TwelveHour);
+        assert_eq!(config.time_format.pattern(), "%I:%M %p");
+
+        assert_eq!(Config::default().time_format.pattern(), "%H:%M");
+    }
+
+    #[test]
+    fn search_weights_keep_defaults_for_missing_fields() {
+        let config: Config = from_str(
+            "server_url = \"http://localhost\"\nusername = \"\"\npassword = \"\"\n[search_weights]\ntype_boost = 5",
+        )
+        .unwrap();
+        assert_eq!(config.search_weights.type_boost, 5);
+        assert_eq!(config.search_weights.recent_boost, 20);
+    }
+
+    #[test]
+    fn endpoint_joins_with_a_single_slash() {
+        let endpoint = |server_url: &str, path: &str| {
+            Config {
+                server_url: server_url.to_string(),
+                ..Default::default()
+            }
+            .endpoint(path)
+        };
+
+        assert_eq!(
+            endpoint("http://foobar.baz:8096", "/Items"),
+            "http://foobar.baz:8096/Items"
+        );
+        assert_eq!(
+            endpoint("http://foobar.baz:8096/", "/Items"),
+            "http://foobar.baz:8096/Items"
+        );
+        assert_eq!(
+            endpoint("http://foobar.baz:8096/jf", "/Items"),
+            "http://foobar.baz:8096/jf/Items"
+        );
+        assert_eq!(
+            endpoint("http://foobar.baz:8096/jf/", "Items"),
+            "http://foobar.baz:8096/jf/Items"
+        );
+        assert_eq!(
+            endpoint("https://foobar.baz/jf//", "/web/#/details?id=1"),
+            "https://foobar.baz/jf/web/#/details?id=1"
+        );
+    }
+
+    #[test]
+    fn normalize_server_url_strips_trailing_slashes() {
+        assert_eq!(
+            normalize_server_url("https://foobar.baz/jf//\n"),
+            "https://foobar.baz/jf"
+        );
+    }
+
+    #[test]
+    fn normalize_server_url_adds_missing_scheme() {
+        assert_eq!(
+            normalize_server_url("foobar.baz:8096"),
+            "http://foobar.baz:8096"
+        );
+    }
+}
@@ -1,22 +1,87 @@
 use ratatui::widgets::Paragraph;
 use ratatui::{layout::Rect, DefaultTerminal, Frame};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{Read, Write};
-use std::os::unix::net::UnixStream;
+use std::future::Future;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Result;
-use directories::BaseDirs;
-use hostname;
+use directories::ProjectDirs;
+use futures_util::future::join_all;
+use itertools::Itertools;
 use reqwest::StatusCode;
 use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::config::{CacheFormat, Config, MarkWatched, ServerType, TimeFormat};
+use crate::opensubtitles::OpenSubtitles;
+use crate::trakt::Trakt;
+use crate::websocket::{self, ServerEvent};
+
+const ITEM_FIELDS: &str =
+    "Path,Overview,CommunityRating,CriticRating,RunTimeTicks,ProviderIds,RemoteTrailers,Genres,DateCreated";
+// ? episodes make up most of a library, their details are fetched on demand with fetch_details
+const EPISODE_FIELDS: &str = "RunTimeTicks,ProviderIds";
+// ? the home pages mix episodes in, which are shown with their series, season and number
+const HOME_FIELDS: &str = "Path,Overview,CommunityRating,CriticRating,RunTimeTicks,ProviderIds,RemoteTrailers,DateCreated,SeriesId,SeriesName,ParentIndexNumber,IndexNumber";
+// ? large libraries are fetched in pages, a single response can time out
+const PAGE_SIZE: usize = 1000;
+const BACKGROUND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ? bumped whenever the cached items change shape, so older caches are fetched again instead of misread
+const CACHE_VERSION: u32 = 1;
+
+// ? numbers every player launched by this process, so sessions never share a socket
+static NEXT_SESSION: AtomicUsize = AtomicUsize::new(0);
+
+// ? caches from before the version tag are plain maps of items, which read as version 0
+#[derive(Debug, Serialize, Deserialize)]
+struct Cache<Items> {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    items: Items,
+}
+
+fn encode_cache(format: CacheFormat, items: &HashMap<String, MediaItem>) -> Result<Vec<u8>> {
+    let cache = Cache {
+        version: CACHE_VERSION,
+        items,
+    };
+
+    Ok(match format {
+        CacheFormat::Json => serde_json::to_vec(&cache)?,
+        // ? with field names, so fields can be added without breaking older caches
+        CacheFormat::Messagepack => rmp_serde::to_vec_named(&cache)?,
+    })
+}
+
+fn decode_cache(format: CacheFormat, bytes: &[u8]) -> Result<Cache<HashMap<String, MediaItem>>> {
+    Ok(match format {
+        CacheFormat::Json => serde_json::from_slice(bytes)?,
+        CacheFormat::Messagepack => rmp_serde::from_slice(bytes)?,
+    })
+}
 
-use crate::config::Config;
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedToken {
+    server_url: String,
+    username: String,
+    access_token: String,
+    #[serde(default)]
+    user_id: Option<String>,
+    #[serde(default)]
+    device_id: Option<String>,
+}
 
 #[derive(Debug, Deserialize, Clone)]
 struct AuthResponse {
@@ -32,6 +97,16 @@ struct JellyfinUser {
     id: String,
     #[serde(rename = "Configuration")]
     config: JellyfinUserConfig,
+    #[serde(rename = "Policy", default)]
+    policy: JellyfinUserPolicy,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+struct JellyfinUserPolicy {
+    #[serde(rename = "EnableContentDeletion", default)]
+    enable_content_deletion: bool,
+    #[serde(rename = "RemoteClientBitrateLimit", default)]
+    remote_client_bitrate_limit: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,10 +119,158 @@ struct JellyfinUserConfig {
     subtitle_language_preference: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct RefreshSummary {
+    pub total: usize,
+    pub new: usize,
+    pub updated_at: chrono::DateTime<chrono::Local>,
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibraryStats {
+    pub movies: usize,
+    pub series: usize,
+    pub episodes: usize,
+    pub watched_movies: usize,
+    pub watched_episodes: usize,
+    pub runtime_ticks: i64,
+    pub watched_ticks: i64,
+}
+
+impl LibraryStats {
+    // ? runtime only counts what can be watched, series have the sum of their episodes as runtime
+    pub fn from_items<'a>(items: impl IntoIterator<Item = &'a MediaItem>) -> Self {
+        let mut stats = LibraryStats::default();
+
+        for item in items {
+            let played = item.user_data.as_ref().is_some_and(|data| data.played);
+            let runtime = item.runtime_ticks.unwrap_or(0);
+
+            match item.type_.as_str() {
+                "Movie" => {
+                    stats.movies += 1;
+                    stats.watched_movies += played as usize;
+                }
+                "Episode" => {
+                    stats.episodes += 1;
+                    stats.watched_episodes += played as usize;
+                }
+                "Series" => {
+                    stats.series += 1;
+                    continue;
+                }
+                _ => continue,
+            }
+
+            stats.runtime_ticks += runtime;
+            if played {
+                stats.watched_ticks += runtime;
+            }
+        }
+
+        stats
+    }
+
+    pub fn watched_percent(&self) -> u64 {
+        match self.runtime_ticks {
+            0 => 0,
+            total => (self.watched_ticks * 100 / total) as u64,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PlayerNotFound(pub String);
+
+impl std::fmt::Display for PlayerNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} not found — install it or set a custom player in config",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for PlayerNotFound {}
+
+#[derive(Debug)]
+pub struct PlayerFailed {
+    pub player: String,
+    pub code: i32,
+    pub output: String,
+    pub log_path: PathBuf,
+}
+
+impl std::fmt::Display for PlayerFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} stopped with exit code {}", self.player, self.code)?;
+        if !self.output.is_empty() {
+            write!(f, ":\n\n{}", self.output)?;
+        }
+        write!(f, "\n\nThe full output is in {}", self.log_path.display())
+    }
+}
+
+impl std::error::Error for PlayerFailed {}
+
+fn last_lines(output: &str, count: usize) -> String {
+    let lines = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+#[derive(Debug, Deserialize)]
+struct PublicSystemInfo {
+    #[serde(rename = "Version")]
+    version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ServerVersion(pub u32, pub u32, pub u32);
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+const MIN_SERVER_VERSION: ServerVersion = ServerVersion(10, 8, 0);
+
+impl std::str::FromStr for ServerVersion {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '.');
+        let mut next = || parts.next().unwrap_or("0").parse::<u32>();
+
+        Ok(ServerVersion(next()?, next()?, next()?))
+    }
+}
+
+impl std::fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Session {
+    #[serde(rename = "DeviceId")]
+    device_id: Option<String>,
+    #[serde(rename = "DeviceName")]
+    device_name: Option<String>,
+    #[serde(rename = "NowPlayingItem")]
+    now_playing_item: Option<MediaItem>,
+}
+
 #[derive(Debug, Deserialize)]
 struct JellyfinItemsResponse {
     #[serde(rename = "Items")]
     items: Vec<MediaItem>,
+    #[serde(rename = "TotalRecordCount", default)]
+    total_record_count: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,13 +279,149 @@ struct PlaybackInfo {
     media_sources: Vec<MediaSource>,
 }
 
-#[derive(Debug, Deserialize)]
-struct MediaSource {
+#[derive(Debug, Deserialize, Clone)]
+pub struct MediaSource {
+    #[serde(rename = "Id")]
+    pub id: String,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Size")]
+    pub size: Option<i64>,
     #[serde(rename = "RunTimeTicks")]
     runtime_ticks: i64,
+    #[serde(rename = "Bitrate")]
+    bitrate: Option<u64>,
+    #[serde(rename = "TranscodingUrl")]
+    transcoding_url: Option<String>,
+    #[serde(rename = "MediaStreams", default)]
+    media_streams: Vec<MediaStream>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct MediaStream {
+    #[serde(rename = "Type")]
+    type_: String,
+    #[serde(rename = "Index", default)]
+    index: i64,
+    #[serde(rename = "Width")]
+    width: Option<i64>,
+    #[serde(rename = "Height")]
+    height: Option<i64>,
+    #[serde(rename = "Language")]
+    language: Option<String>,
+    #[serde(rename = "IsForced", default)]
+    is_forced: bool,
+    #[serde(rename = "IsExternal", default)]
+    is_external: bool,
+}
+
+impl MediaSource {
+    pub fn resolution(&self) -> Option<String> {
+        self.media_streams
+            .iter()
+            .find(|stream| stream.type_ == "Video")
+            .and_then(|stream| Some(format!("{}x{}", stream.width?, stream.height?)))
+    }
+
+    // ? mpv numbers subtitle tracks from 1 in the order they appear in the file, external ones aren't part of it
+    pub fn forced_subtitle_track(&self, language: &str) -> Option<usize> {
+        let subtitles: Vec<_> = self
+            .media_streams
+            .iter()
+            .filter(|stream| stream.type_ == "Subtitle" && !stream.is_external)
+            .sorted_by_key(|stream| stream.index)
+            .collect();
+
+        let forced = || {
+            subtitles
+                .iter()
+                .enumerate()
+                .filter(|(_, stream)| stream.is_forced)
+        };
+
+        forced()
+            .find(|(_, stream)| stream.language.as_deref() == Some(language))
+            .or_else(|| forced().next())
+            .map(|(position, _)| position + 1)
+    }
+
+    // ? external subtitles are files next to the media, which mpv doesn't get from the stream url
+    pub fn has_embedded_subtitles(&self) -> bool {
+        self.media_streams
+            .iter()
+            .any(|stream| stream.type_ == "Subtitle" && !stream.is_external)
+    }
+
+    pub fn label(&self) -> String {
+        let mut parts = vec![self.name.clone().unwrap_or_else(|| "Unknown".to_string())];
+
+        if let Some(resolution) = self.resolution() {
+            parts.push(resolution);
+        }
+
+        if let Some(size) = self.size {
+            parts.push(format!("{:.1} GB", size as f64 / 1_000_000_000.0));
+        }
+
+        parts.join(" - ")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaSegmentsResponse {
+    #[serde(rename = "Items")]
+    items: Vec<MediaSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaSegment {
+    #[serde(rename = "Type")]
+    type_: String,
+    #[serde(rename = "StartTicks")]
+    start_ticks: i64,
+    #[serde(rename = "EndTicks")]
+    end_ticks: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Chapter {
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "StartPositionTicks")]
+    pub start_position_ticks: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NameIdPair {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Id")]
+    pub id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MediaUrl {
+    #[serde(rename = "Url")]
+    pub url: String,
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UserData {
+    #[serde(rename = "Played", default)]
+    pub played: bool,
+    #[serde(rename = "PlaybackPositionTicks", default)]
+    pub playback_position_ticks: i64,
+    #[serde(rename = "UnplayedItemCount")]
+    pub unplayed_item_count: Option<i64>,
+    #[serde(rename = "LastPlayedDate")]
+    pub last_played_date: Option<String>,
+    #[serde(rename = "IsFavorite", default)]
+    pub is_favorite: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MediaItem {
     #[serde(rename = "Id")]
     pub id: String,
@@ -72,10 +431,16 @@ pub struct MediaItem {
     pub type_: String,
     #[serde(rename = "Path")]
     pub path: Option<String>,
+    #[serde(rename = "LocationType")]
+    pub location_type: Option<String>,
     #[serde(rename = "CollectionType")]
     pub collection_type: Option<String>,
     #[serde(rename = "ProductionYear")]
     pub year: Option<i32>,
+    #[serde(rename = "PremiereDate")]
+    pub premiere_date: Option<String>,
+    #[serde(rename = "DateCreated")]
+    pub date_created: Option<String>,
     #[serde(rename = "Overview")]
     pub overview: Option<String>,
     #[serde(rename = "CommunityRating")]
@@ -92,102 +457,447 @@ pub struct MediaItem {
     pub parent_index_number: Option<i64>,
     #[serde(rename = "IndexNumber")]
     pub index_number: Option<i64>,
+    #[serde(rename = "ProviderIds", default)]
+    pub provider_ids: HashMap<String, String>,
+    #[serde(rename = "RemoteTrailers", default)]
+    pub remote_trailers: Vec<MediaUrl>,
+    #[serde(rename = "Genres", default)]
+    pub genres: Vec<String>,
+    #[serde(rename = "Chapters", default)]
+    pub chapters: Vec<Chapter>,
+    #[serde(rename = "Album")]
+    pub album: Option<String>,
+    #[serde(rename = "AlbumId")]
+    pub album_id: Option<String>,
+    #[serde(rename = "Artists", default)]
+    pub artists: Vec<String>,
+    #[serde(rename = "AlbumArtists", default)]
+    pub album_artists: Vec<NameIdPair>,
+    #[serde(rename = "UserData")]
+    pub user_data: Option<UserData>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Jellyfin {
-    pub items: HashMap<String, MediaItem>,
-    pub continue_watching: Vec<MediaItem>,
-    pub next_up: Vec<MediaItem>,
-    pub latest_added: Vec<MediaItem>,
+    pub(crate) items: HashMap<String, MediaItem>,
+    pub(crate) continue_watching: Vec<MediaItem>,
+    pub(crate) next_up: Vec<MediaItem>,
+    pub(crate) latest_added: Vec<MediaItem>,
+    pub(crate) playlists: Vec<MediaItem>,
+    playlist_items: HashMap<String, Vec<MediaItem>>,
+    pub(crate) libraries: Vec<MediaItem>,
+    library_latest: HashMap<String, Vec<MediaItem>>,
+    // ? collection names by the items they contain, fetched once when the first movie plays
+    collections: Option<HashMap<String, String>>,
     client: Client,
     config: Config,
     auth: Option<AuthResponse>,
-    mpv_processes: Arc<Mutex<Vec<Child>>>,
+    mpv_sessions: Arc<Mutex<HashMap<String, Child>>>,
+    // ? the most recently started player, which remote control commands go to
+    mpv_socket: Arc<Mutex<Option<String>>>,
+    fetch_progress: Arc<Mutex<(usize, usize)>>,
     cache_path: PathBuf,
+    data_dir: PathBuf,
+    server_version: Option<ServerVersion>,
+    trakt: Option<Trakt>,
+    opensubtitles: Option<OpenSubtitles>,
+}
+
+// ? stops the players while the client itself is busy monitoring one
+pub struct PlaybackControl {
+    mpv_sessions: Arc<Mutex<HashMap<String, Child>>>,
+    mpv_socket: Arc<Mutex<Option<String>>>,
+}
+
+impl PlaybackControl {
+    // ? the monitored player is asked to quit, so its last position is still reported,
+    // ? anything else, like a trailer, is stopped right away
+    pub(crate) fn stop(&self) -> Result<()> {
+        let monitored = self.mpv_socket.lock().unwrap().clone();
+
+        let quitting = monitored.filter(|socket_path| {
+            std::os::unix::net::UnixStream::connect(socket_path)
+                .and_then(|mut socket| socket.write_all(b"{\"command\":[\"quit\"]}\n"))
+                .is_ok()
+        });
+
+        let mut sessions = self.mpv_sessions.lock().unwrap();
+        let others: Vec<_> = sessions
+            .keys()
+            .filter(|socket_path| Some(*socket_path) != quitting.as_ref())
+            .cloned()
+            .collect();
+
+        for socket_path in others {
+            if let Some(mut process) = sessions.remove(&socket_path) {
+                process.kill()?;
+                process.wait()?;
+            }
+            remove_socket(&socket_path)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn next_episode(episodes: &[MediaItem], current: &MediaItem) -> Option<MediaItem> {
+    let season = current.parent_index_number.unwrap_or(0);
+    let index = current.index_number.unwrap_or(0);
+
+    episodes
+        .iter()
+        .filter(|ep| {
+            ep.parent_index_number.unwrap_or(0) == season && ep.index_number.unwrap_or(0) > index
+        })
+        .min_by_key(|ep| ep.index_number.unwrap_or(0))
+        .or_else(|| {
+            episodes
+                .iter()
+                .filter(|ep| ep.parent_index_number.unwrap_or(0) > season)
+                .min_by_key(|ep| {
+                    (
+                        ep.parent_index_number.unwrap_or(0),
+                        ep.index_number.unwrap_or(0),
+                    )
+                })
+        })
+        .cloned()
+}
+
+pub fn episodes_before(episodes: &[MediaItem], current: &MediaItem) -> Vec<MediaItem> {
+    let key = |ep: &MediaItem| {
+        (
+            ep.parent_index_number.unwrap_or(0),
+            ep.index_number.unwrap_or(0),
+        )
+    };
+
+    let mut before: Vec<_> = episodes
+        .iter()
+        .filter(|ep| key(ep) < key(current))
+        .cloned()
+        .collect();
+
+    before.sort_by_key(key);
+
+    before
+}
+
+fn playing_elsewhere(sessions: Vec<Session>, device_id: &str) -> HashMap<String, Vec<String>> {
+    let mut playing: HashMap<String, Vec<String>> = HashMap::new();
+
+    for session in sessions {
+        if session.device_id.as_deref() == Some(device_id) {
+            continue;
+        }
+
+        let Some(item) = session.now_playing_item else {
+            continue;
+        };
+
+        let device = session
+            .device_name
+            .unwrap_or_else(|| "unknown device".to_string());
+
+        for id in [Some(item.id), item.series_id].into_iter().flatten() {
+            playing.entry(id).or_default().push(device.clone());
+        }
+    }
+
+    playing
+}
+
+async fn response_items(response: Result<Response>) -> Result<Vec<MediaItem>> {
+    Ok(response?
+        .error_for_status()?
+        .json::<JellyfinItemsResponse>()
+        .await?
+        .items)
+}
+
+// ? one home section failing leaves it empty instead of failing the whole home page
+async fn items_or_empty(
+    response: Result<Response>,
+    section: &str,
+    failed: &mut Vec<String>,
+) -> Vec<MediaItem> {
+    response_items(response).await.unwrap_or_else(|e| {
+        failed.push(format!("{}: {}", section, e));
+        Vec::new()
+    })
+}
+
+// ? fills in series info the server left out from the cached copy, so episodes never show up as "Unknown Series"
+fn backfill_series_context(items: &mut [MediaItem], cache: &HashMap<String, MediaItem>) {
+    for item in items.iter_mut().filter(|item| item.type_ == "Episode") {
+        let Some(cached) = cache.get(&item.id) else {
+            continue;
+        };
+
+        if item.series_id.is_none() {
+            item.series_id = cached.series_id.clone();
+        }
+        if item.series_name.is_none() {
+            item.series_name = cached.series_name.clone();
+        }
+        if item.parent_index_number.is_none() {
+            item.parent_index_number = cached.parent_index_number;
+        }
+        if item.index_number.is_none() {
+            item.index_number = cached.index_number;
+        }
+    }
+}
+
+pub fn format_ticks(ticks: i64) -> String {
+    let total_minutes = ticks / (10_000_000 * 60);
+    let hours = total_minutes / 60;
+    let minutes = total_minutes % 60;
+
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+// ? the same item fetched at different times can differ in its user data, it is still the same item
+impl PartialEq for MediaItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for MediaItem {}
+
+impl std::hash::Hash for MediaItem {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
 
 impl MediaItem {
     pub fn format_runtime(&self) -> String {
-        let Some(ticks) = self.runtime_ticks else {
-            return "Unknown runtime".to_string();
-        };
+        match self.runtime_ticks {
+            Some(ticks) => format_ticks(ticks),
+            None => "Unknown runtime".to_string(),
+        }
+    }
+
+    pub fn imdb_id(&self) -> Option<&str> {
+        self.provider_ids.get("Imdb").map(String::as_str)
+    }
 
-        let total_minutes = (ticks / (10_000_000 * 60)) as i64;
-        let hours = total_minutes / 60;
-        let minutes = total_minutes % 60;
+    pub fn tmdb_id(&self) -> Option<&str> {
+        self.provider_ids.get("Tmdb").map(String::as_str)
+    }
 
-        if hours > 0 {
-            format!("{}h {}m", hours, minutes)
-        } else {
-            format!("{}m", minutes)
+    // ? imdb first since it has pages for episodes too, tmdb episode ids need their series in the url
+    pub fn external_url(&self) -> Option<String> {
+        if let Some(id) = self.imdb_id() {
+            return Some(format!("https://www.imdb.com/title/{}/", id));
         }
+
+        let kind = match self.type_.as_str() {
+            "Movie" => "movie",
+            "Series" => "tv",
+            _ => return None,
+        };
+
+        self.tmdb_id()
+            .map(|id| format!("https://www.themoviedb.org/{}/{}", kind, id))
+    }
+
+    pub fn trailer_url(&self) -> Option<&str> {
+        self.remote_trailers
+            .iter()
+            .map(|trailer| trailer.url.as_str())
+            .find(|url| !url.is_empty())
     }
 
-    pub fn format_end_time(&self) -> String {
+    // ? virtual items are placeholders, e.g. for missing or unaired episodes, without a file to play
+    pub fn is_playable(&self) -> bool {
+        self.location_type.as_deref() != Some("Virtual")
+    }
+
+    pub fn remaining_ticks(&self) -> i64 {
+        let position = self
+            .user_data
+            .as_ref()
+            .map_or(0, |data| data.playback_position_ticks);
+
+        self.runtime_ticks
+            .unwrap_or(0)
+            .saturating_sub(position)
+            .max(0)
+    }
+
+    pub fn format_end_time(&self, time_format: TimeFormat) -> String {
         let Some(ticks) = self.runtime_ticks else {
             return "Unknown runtime".to_string();
         };
 
         (chrono::Local::now() + chrono::Duration::seconds(ticks / 10_000_000))
-            .format("%H:%M")
+            .format(time_format.pattern())
             .to_string()
     }
 }
 
+// ? embedding jellytui's browsing in another app:
+// ? 1. Jellyfin::from_config builds a client without touching the network
+// ? 2. login (or authenticate, which skips the cached token) must succeed before anything else that talks to the server
+// ? 3. fetch_all_media fills the library (from the cache when there is one), fetch_home_sections the home pages
+// ? 4. items, get_episodes_from_series and the other getters only read what was fetched and are always safe to call
+// ? Jellyfin::new does all of this with progress output, which is what run_app uses
 impl Jellyfin {
-    pub async fn new(
-        base_path: Option<&Path>,
-        config: Config,
-        opt_terminal: &mut Option<&mut DefaultTerminal>,
-        render_outer: impl Fn(&mut Frame) -> Rect,
-    ) -> Result<Self> {
-        // cache directory init
-        let cache_path = base_path
-            .map(|p| p.join("cache.json"))
-            .or(BaseDirs::new().map(|base_dirs| {
-                base_dirs
-                    .data_local_dir()
-                    .join("jellytui")
-                    .join("cache.json")
-            }))
-            .unwrap();
-
-        if let Some(parent) = cache_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let mut jellyfin = Jellyfin {
+    pub fn from_config(base_path: Option<&Path>, mut config: Config) -> Result<Self> {
+        // ? the cache can be rebuilt at any time, so it lives in the cache dir ($XDG_CACHE_HOME on linux),
+        // ? while the state and token are kept in the data dir
+        let (cache_dir, data_dir) = match base_path {
+            Some(path) => (path.to_path_buf(), path.to_path_buf()),
+            None => {
+                let dirs = ProjectDirs::from("", "", "jellytui")
+                    .ok_or_else(|| anyhow::anyhow!("Could not determine cache directory"))?;
+
+                // the cache used to be kept in the data dir
+                let _ = fs::remove_file(dirs.data_local_dir().join("cache.json"));
+
+                (
+                    dirs.cache_dir().to_path_buf(),
+                    dirs.data_local_dir().to_path_buf(),
+                )
+            }
+        };
+
+        std::fs::create_dir_all(&cache_dir)?;
+        std::fs::create_dir_all(&data_dir)?;
+
+        config.load_device_id(&data_dir);
+
+        // ? a cache in the other format is left over from switching, and would only go stale
+        let cache_format = config.cache_format;
+        for format in [CacheFormat::Json, CacheFormat::Messagepack] {
+            if format != cache_format {
+                let _ = fs::remove_file(cache_dir.join(format.file_name()));
+            }
+        }
+
+        let cache_path = cache_dir.join(cache_format.file_name());
+
+        Ok(Jellyfin {
             items: HashMap::new(),
             continue_watching: Vec::new(),
             next_up: Vec::new(),
             latest_added: Vec::new(),
-            client: Client::builder()
-                .danger_accept_invalid_certs(config.accept_self_signed)
-                .build()?,
+            playlists: Vec::new(),
+            playlist_items: HashMap::new(),
+            libraries: Vec::new(),
+            library_latest: HashMap::new(),
+            collections: None,
+            client: config.client()?,
+            trakt: config.trakt.clone().map(Trakt::new).transpose()?,
+            opensubtitles: config
+                .subtitles
+                .clone()
+                .map(OpenSubtitles::new)
+                .transpose()?,
             config,
             auth: None,
-            mpv_processes: Arc::new(Mutex::new(Vec::new())),
+            mpv_sessions: Arc::new(Mutex::new(HashMap::new())),
+            mpv_socket: Arc::new(Mutex::new(None)),
+            fetch_progress: Arc::new(Mutex::new((0, 0))),
             cache_path,
-        };
+            data_dir,
+            server_version: None,
+        })
+    }
+
+    pub async fn new(
+        base_path: Option<&Path>,
+        config: Config,
+        opt_terminal: &mut Option<&mut DefaultTerminal>,
+        render_outer: impl Fn(&mut Frame) -> Rect,
+    ) -> Result<Self> {
+        let mut jellyfin = Jellyfin::from_config(base_path, config)?;
+
+        let mut warnings: Vec<String> = Vec::new();
         macro_rules! log {
-            ($txt:expr) => {
+            ($($arg:tt)*) => {
+                let text = format!($($arg)*);
                 match opt_terminal {
                     Some(terminal) => {
+                        let lines = [warnings.clone(), vec![text]].concat().join("\n");
                         terminal.draw(|frame| {
                             let inner_area = render_outer(frame);
-                            frame.render_widget(Paragraph::new($txt), inner_area);
+                            frame.render_widget(Paragraph::new(lines), inner_area);
                         })?;
                     }
                     None => {
-                        println!($txt);
+                        println!("{}", text);
                     }
                 }
             };
         }
-        log!("Authenticating...");
+        macro_rules! warn {
+            ($($arg:tt)*) => {
+                let warning = format!("Warning: {}", format!($($arg)*));
+                if opt_terminal.is_none() {
+                    println!("{}", warning);
+                }
+                warnings.push(warning);
+            };
+        }
+        macro_rules! log_while {
+            ($future:expr, $txt:expr) => {{
+                let future = $future;
+                match opt_terminal {
+                    Some(_) => {
+                        let start = std::time::Instant::now();
+                        let mut interval = tokio::time::interval(Duration::from_millis(100));
+                        tokio::pin!(future);
+                        loop {
+                            tokio::select! {
+                                result = &mut future => break result,
+                                _ = interval.tick() => {
+                                    let spinner = SPINNER[(start.elapsed().as_millis() / 100) as usize % SPINNER.len()];
+                                    log!("{} {} ({}s)", spinner, $txt, start.elapsed().as_secs());
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        log!("{}", $txt);
+                        future.await
+                    }
+                }
+            }};
+        }
+        log!("Checking server version...");
+
+        // ? emby is versioned separately, so its version says nothing about what is supported
+        match jellyfin.fetch_server_version().await {
+            _ if jellyfin.config.server_type == ServerType::Emby => {}
+            Ok(version) if version < MIN_SERVER_VERSION => {
+                warn!(
+                    "Jellyfin {} is older than the minimum supported version {}, some features may not work",
+                    version, MIN_SERVER_VERSION
+                );
+                jellyfin.server_version = Some(version);
+            }
+            Ok(version) => jellyfin.server_version = Some(version),
+            Err(e) => {
+                warn!("could not determine server version: {}", e);
+            }
+        }
+
+        if !jellyfin.config.player_installed() {
+            warn!(
+                "{} not found, install it or set a custom player in config to play media",
+                jellyfin.config.player()
+            );
+        }
 
-        match jellyfin.authenticate().await {
+        match log_while!(jellyfin.login(), "Authenticating...") {
             Ok(_) => {}
             Err(e) => {
                 eprintln!("Failed to authenticate: {}", e);
@@ -209,55 +919,256 @@ impl Jellyfin {
                 std::process::exit(1);
             }
         }
-        log!("Fetching media... this may take a while on the first run");
-        jellyfin.fetch_all_media().await?;
-        log!("Fetching home sections...");
-        jellyfin.fetch_home_sections().await?;
+        for arg in jellyfin.config.reserved_mpv_args() {
+            warn!(
+                "ignoring mpv argument {}, jellytui needs its own ipc socket to track playback",
+                arg
+            );
+        }
+
+        if let Err(e) = jellyfin.register_capabilities().await {
+            warn!("could not register session: {}", e);
+        }
+
+        let progress = jellyfin.fetch_progress.clone();
+        log_while!(jellyfin.fetch_all_media(), {
+            match *progress.lock().unwrap() {
+                (_, 0) => "Fetching media... this may take a while on the first run".to_string(),
+                (fetched, total) => format!("Fetching media... fetched {}/{}", fetched, total),
+            }
+        })?;
+        for failed in log_while!(jellyfin.fetch_home_sections(), "Fetching home sections...")? {
+            warn!("could not fetch {}", failed);
+        }
 
         Ok(jellyfin)
     }
 
-    async fn request(&mut self, request: RequestBuilder) -> Result<Response> {
-        let response = request
-            .try_clone()
-            .expect("Failed to clone request")
-            .header(
-                "X-MediaBrowser-Token",
-                &self.auth.as_ref().unwrap().access_token,
-            )
-            .send()
-            .await?;
+    pub(crate) fn connect_websocket(&self) -> UnboundedReceiver<ServerEvent> {
+        websocket::spawn(
+            &self
+                .config
+                .endpoint(self.config.server_type.websocket_path()),
+            &self.auth.as_ref().unwrap().access_token,
+            self.config.device_id(),
+            self.mpv_socket.clone(),
+        )
+    }
 
-        if response.status() != StatusCode::UNAUTHORIZED {
-            return Ok(response);
-        }
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
 
-        self.authenticate().await?;
+    pub fn web_url(&self, item: &MediaItem) -> String {
+        self.config.endpoint(&format!(
+            "{}{}",
+            self.config.server_type.details_path(),
+            item.id
+        ))
+    }
 
-        Ok(request
-            .header(
-                "X-MediaBrowser-Token",
+    pub fn items(&self) -> impl Iterator<Item = &MediaItem> {
+        self.items.values()
+    }
+
+    pub fn continue_watching(&self) -> &[MediaItem] {
+        &self.continue_watching
+    }
+
+    pub fn next_up(&self) -> &[MediaItem] {
+        &self.next_up
+    }
+
+    pub fn latest_added(&self) -> &[MediaItem] {
+        &self.latest_added
+    }
+
+    pub fn library_stats(&self) -> LibraryStats {
+        LibraryStats::from_items(self.items.values())
+    }
+
+    pub fn latest_in_library(&self, library_id: &str) -> Option<&Vec<MediaItem>> {
+        self.library_latest.get(library_id)
+    }
+
+    pub fn get_playlist_items(&self, playlist_id: &str) -> Vec<MediaItem> {
+        self.playlist_items
+            .get(playlist_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn server_version(&self) -> Option<ServerVersion> {
+        self.server_version
+    }
+
+    pub(crate) fn state_path(&self) -> PathBuf {
+        self.data_dir.join("state.json")
+    }
+
+    async fn request(&mut self, request: RequestBuilder) -> Result<Response> {
+        let response = request
+            .try_clone()
+            .expect("Failed to clone request")
+            .header(
+                self.config.server_type.token_header(),
+                &self.auth.as_ref().unwrap().access_token,
+            )
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        self.authenticate().await?;
+
+        Ok(request
+            .header(
+                self.config.server_type.token_header(),
                 &self.auth.as_ref().unwrap().access_token,
             )
             .send()
             .await?)
     }
 
-    async fn authenticate(&mut self) -> Result<()> {
-        let device_name = hostname::get()
-            .map(|h| h.to_string_lossy().to_string())
-            .unwrap_or_else(|_| "unknown-device".to_string());
+    // ? for requests spawned off the main loop, which can't log in again when the token expired
+    fn background_request(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .header(
+                self.config.server_type.token_header(),
+                &self.auth.as_ref().unwrap().access_token,
+            )
+            .timeout(BACKGROUND_TIMEOUT)
+    }
+
+    // ? sends a batch at once, the ones turned away for an expired token are sent again after logging in
+    async fn request_all(&mut self, requests: Vec<RequestBuilder>) -> Vec<Result<Response>> {
+        let token = self.auth.as_ref().unwrap().access_token.clone();
+        let header = self.config.server_type.token_header();
+
+        let responses = join_all(requests.iter().map(|request| {
+            request
+                .try_clone()
+                .expect("Failed to clone request")
+                .header(header, &token)
+                .send()
+        }))
+        .await;
+
+        let mut results = Vec::new();
+        for (request, response) in requests.into_iter().zip(responses) {
+            results.push(match response {
+                Ok(response) if response.status() == StatusCode::UNAUTHORIZED => {
+                    self.request(request).await
+                }
+                response => response.map_err(Into::into),
+            });
+        }
+
+        results
+    }
+
+    async fn fetch_server_version(&self) -> Result<ServerVersion> {
+        let info = self
+            .client
+            .get(self.config.endpoint("/System/Info/Public"))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<PublicSystemInfo>()
+            .await?;
+
+        info.version
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Unknown version format: {}", info.version))
+    }
+
+    // ? reuses the token from the last run when it is still valid, and only sends the password otherwise
+    pub async fn login(&mut self) -> Result<()> {
+        if self.restore_session().await.is_ok() {
+            return Ok(());
+        }
+
+        self.authenticate().await
+    }
+
+    fn token_path(&self) -> PathBuf {
+        self.data_dir.join("token.json")
+    }
+
+    async fn restore_session(&mut self) -> Result<()> {
+        let token: CachedToken = serde_json::from_str(&fs::read_to_string(self.token_path())?)?;
+
+        if token.server_url != self.config.server_url || token.username != self.config.username {
+            return Err(anyhow::anyhow!("Cached token belongs to another account"));
+        }
 
+        // ? tokens are tied to a device on the server, so one issued to another device id is replaced
+        if token.device_id.as_deref() != Some(self.config.device_id()) {
+            return Err(anyhow::anyhow!("Cached token belongs to another device"));
+        }
+
+        let Some(path) = self
+            .config
+            .server_type
+            .current_user_path(token.user_id.as_deref())
+        else {
+            return Err(anyhow::anyhow!("Cached token has no user id"));
+        };
+
+        // ? also picks up changes to the user's preferences and permissions since the last run
+        let user = self
+            .client
+            .get(self.config.endpoint(&path))
+            .header(self.config.server_type.token_header(), &token.access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JellyfinUser>()
+            .await?;
+
+        self.auth = Some(AuthResponse {
+            access_token: token.access_token,
+            user,
+        });
+
+        Ok(())
+    }
+
+    fn save_token(&self) -> Result<()> {
+        let token = CachedToken {
+            server_url: self.config.server_url.clone(),
+            username: self.config.username.clone(),
+            access_token: self.auth.as_ref().unwrap().access_token.clone(),
+            user_id: Some(self.auth.as_ref().unwrap().user.id.clone()),
+            device_id: Some(self.config.device_id().to_string()),
+        };
+
+        // the token grants full access to the account, so only the owner may read it
+        fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(self.token_path())?
+            .write_all(serde_json::to_string(&token)?.as_bytes())?;
+
+        Ok(())
+    }
+
+    pub async fn authenticate(&mut self) -> Result<()> {
         let auth_request = serde_json::json!({
             "Username": self.config.username,
             "Pw": self.config.password
         });
 
         let response = self.client
-            .post(format!("{}/Users/AuthenticateByName", self.config.server_url))
+            .post(self.config.endpoint("/Users/AuthenticateByName"))
             .header("X-Emby-Authorization", format!(
-                "MediaBrowser Client=\"jellytui\", Device=\"{}\", DeviceId=\"tui\", Version=\"1.0.0\"",
-                device_name
+                "MediaBrowser Client=\"jellytui\", Device=\"{}\", DeviceId=\"{}\", Version=\"1.0.0\"",
+                self.config.device_name(),
+                self.config.device_id()
             ))
             .json(&auth_request)
             .send().await?;
@@ -274,65 +1185,330 @@ impl Jellyfin {
 
         self.auth = Some(response.json::<AuthResponse>().await?);
 
+        if let Err(e) = self.save_token() {
+            eprintln!("Failed to save access token: {}", e);
+        }
+
+        Ok(())
+    }
+
+    // ? makes this client show up as an active device in the web ui and other apps
+    async fn register_capabilities(&mut self) -> Result<()> {
+        self.request(
+            self.client
+                .post(self.config.endpoint("/Sessions/Capabilities/Full"))
+                .json(&serde_json::json!({
+                    "PlayableMediaTypes": ["Video", "Audio"],
+                    "SupportedCommands": ["SetVolume", "ToggleMute"],
+                    "SupportsMediaControl": true
+                })),
+        )
+        .await?
+        .error_for_status()?;
+
         Ok(())
     }
 
-    async fn fetch_all_media(&mut self) -> Result<()> {
-        if let Ok(cached) = fs::read_to_string(&self.cache_path) {
-            if let Ok(items) = serde_json::from_str::<HashMap<String, MediaItem>>(&cached) {
-                self.items = items;
-                return Ok(());
+    pub async fn fetch_all_media(&mut self) -> Result<()> {
+        if let Ok(cached) = fs::read(&self.cache_path) {
+            match decode_cache(self.config.cache_format, &cached) {
+                Ok(cache) if cache.version == CACHE_VERSION => {
+                    self.items = cache.items;
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("Cache is corrupt, fetching everything again: {}", e);
+                    fs::remove_file(&self.cache_path)?;
+                }
             }
         }
 
-        self.items = self
+        *self.fetch_progress.lock().unwrap() = (0, 0);
+
+        let mut items = self
+            .fetch_items("Movie,Series,MusicArtist,MusicAlbum,Audio", ITEM_FIELDS)
+            .await?;
+        items.extend(self.fetch_items("Episode", EPISODE_FIELDS).await?);
+
+        self.items = items
+            .into_iter()
+            .map(|item| (item.id.clone(), item))
+            .collect();
+
+        self.save_cache()
+    }
+
+    // ? the server only sends the ids of changed items, so they are fetched again one page at a time
+    pub(crate) async fn fetch_items_by_id(&mut self, ids: &[String]) -> Result<Vec<MediaItem>> {
+        let endpoint = self.config.endpoint(&format!(
+            "/Users/{}/Items",
+            &self.auth.as_ref().unwrap().user.id
+        ));
+
+        let requests = ids
+            .chunks(100)
+            .map(|ids| {
+                self.client.get(&endpoint).query(&[
+                    ("Ids", ids.join(",").as_str()),
+                    ("Fields", ITEM_FIELDS),
+                    ("EnableUserData", "true"),
+                    (
+                        "IncludeItemTypes",
+                        "Movie,Series,MusicArtist,MusicAlbum,Audio,Episode",
+                    ),
+                ])
+            })
+            .collect();
+
+        let mut items = Vec::new();
+        for response in self.request_all(requests).await {
+            items.extend(
+                response?
+                    .error_for_status()?
+                    .json::<JellyfinItemsResponse>()
+                    .await?
+                    .items,
+            );
+        }
+
+        Ok(items)
+    }
+
+    pub(crate) fn apply_library_changes(
+        &mut self,
+        changed: Vec<MediaItem>,
+        removed: &[String],
+    ) -> Result<()> {
+        for id in removed {
+            self.items.remove(id);
+        }
+        self.items
+            .extend(changed.into_iter().map(|item| (item.id.clone(), item)));
+
+        self.save_cache()
+    }
+
+    async fn fetch_items(&mut self, types: &str, fields: &str) -> Result<Vec<MediaItem>> {
+        let mut items = Vec::new();
+
+        loop {
+            let page = self
+                .request(
+                    self.client
+                        .get(self.config.endpoint(&format!(
+                            "/Users/{}/Items",
+                            &self.auth.as_ref().unwrap().user.id
+                        )))
+                        .query(&[
+                            ("Recursive", "true"),
+                            ("Fields", fields),
+                            ("EnableUserData", "true"),
+                            ("IncludeItemTypes", types),
+                            ("SortBy", "SortName"),
+                            ("SortOrder", "Ascending"),
+                            ("StartIndex", &items.len().to_string()),
+                            ("Limit", &PAGE_SIZE.to_string()),
+                        ]),
+                )
+                .await?
+                .json::<JellyfinItemsResponse>()
+                .await?;
+
+            // ? the total is added once per item type, so progress covers every call
+            let mut progress = self.fetch_progress.lock().unwrap();
+            if items.is_empty() {
+                progress.1 += page.total_record_count;
+            }
+            progress.0 += page.items.len();
+            drop(progress);
+
+            let done = page.items.len() < PAGE_SIZE;
+            items.extend(page.items);
+
+            if done || items.len() >= page.total_record_count {
+                return Ok(items);
+            }
+        }
+    }
+
+    pub(crate) fn fetch_playing_elsewhere(
+        &self,
+    ) -> impl Future<Output = Result<HashMap<String, Vec<String>>>> + Send + 'static {
+        let request = self.background_request(
+            self.client
+                .get(self.config.endpoint("/Sessions"))
+                .query(&[("ActiveWithinSeconds", "60")]),
+        );
+        let device_id = self.config.device_id().to_string();
+
+        async move {
+            let sessions = request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<Session>>()
+                .await?;
+
+            Ok(playing_elsewhere(sessions, &device_id))
+        }
+    }
+
+    pub async fn fetch_details(&mut self, item: &MediaItem) -> Result<MediaItem> {
+        Ok(self
+            .request(self.details_request(&item.id))
+            .await?
+            .json::<MediaItem>()
+            .await?)
+    }
+
+    pub(crate) fn fetch_details_in_background(
+        &self,
+        item_id: &str,
+    ) -> impl Future<Output = Result<MediaItem>> + Send + 'static {
+        let request = self.background_request(self.details_request(item_id));
+
+        async move {
+            Ok(request
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<MediaItem>()
+                .await?)
+        }
+    }
+
+    fn details_request(&self, item_id: &str) -> RequestBuilder {
+        self.client
+            .get(self.config.endpoint(&format!(
+                "/Users/{}/Items/{}",
+                &self.auth.as_ref().unwrap().user.id,
+                item_id
+            )))
+            .query(&[("Fields", ITEM_FIELDS)])
+    }
+
+    // ? always as jpeg, which is the only format decoded for display
+    pub(crate) fn fetch_primary_image(
+        &self,
+        item_id: &str,
+        max_width: u32,
+    ) -> impl Future<Output = Result<Vec<u8>>> + Send + 'static {
+        let request = self.background_request(
+            self.client
+                .get(
+                    self.config
+                        .endpoint(&format!("/Items/{}/Images/Primary", item_id)),
+                )
+                .query(&[
+                    ("maxWidth", max_width.to_string().as_str()),
+                    ("format", "Jpg"),
+                ]),
+        );
+
+        async move {
+            let response = request.send().await?.error_for_status()?;
+
+            Ok(response.bytes().await?.to_vec())
+        }
+    }
+
+    // ? by season number, seasons can have names of their own like "Specials" or a story arc
+    pub async fn fetch_season_names(&mut self, series_id: &str) -> Result<HashMap<i64, String>> {
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        let seasons = self
             .request(
                 self.client
-                    .get(format!(
-                        "{}/Users/{}/Items",
-                        self.config.server_url,
-                        &self.auth.as_ref().unwrap().user.id
-                    ))
+                    .get(
+                        self.config
+                            .endpoint(&format!("/Shows/{}/Seasons", series_id)),
+                    )
+                    .query(&[("UserId", user_id.as_str())]),
+            )
+            .await?
+            .error_for_status()?
+            .json::<JellyfinItemsResponse>()
+            .await?
+            .items;
+
+        Ok(seasons
+            .into_iter()
+            .filter_map(|season| season.index_number.map(|index| (index, season.name)))
+            .collect())
+    }
+
+    // ? series added since the cache was built have no episodes in it, those are fetched when opened
+    pub async fn fetch_episodes(&mut self, series_id: &str) -> Result<Vec<MediaItem>> {
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        let episodes = self
+            .request(
+                self.client
+                    .get(
+                        self.config
+                            .endpoint(&format!("/Shows/{}/Episodes", series_id)),
+                    )
                     .query(&[
-                        ("Recursive", "true"),
-                        (
-                            "Fields",
-                            "Path,Overview,CommunityRating,CriticRating,RunTimeTicks",
-                        ),
-                        ("IncludeItemTypes", "Movie,Series,Episode"),
-                        ("SortBy", "SortName"),
-                        ("SortOrder", "Ascending"),
+                        ("UserId", user_id.as_str()),
+                        ("Fields", EPISODE_FIELDS),
+                        ("EnableUserData", "true"),
                     ]),
             )
             .await?
+            .error_for_status()?
             .json::<JellyfinItemsResponse>()
             .await?
-            .items
-            .into_iter()
-            .map(|item| (item.id.clone(), item))
-            .collect();
+            .items;
 
-        fs::write(&self.cache_path, serde_json::to_string(&self.items)?)?;
+        if !episodes.is_empty() {
+            self.items.extend(
+                episodes
+                    .into_iter()
+                    .map(|episode| (episode.id.clone(), episode)),
+            );
+            self.save_cache()?;
+        }
 
-        Ok(())
+        Ok(self.get_episodes_from_series(series_id))
+    }
+
+    pub async fn refresh_item(&mut self, item: &MediaItem) -> Result<MediaItem> {
+        let fresh = self.fetch_details(item).await?;
+
+        for list in [
+            &mut self.continue_watching,
+            &mut self.next_up,
+            &mut self.latest_added,
+        ] {
+            for other in list.iter_mut().filter(|other| other.id == fresh.id) {
+                *other = fresh.clone();
+            }
+        }
+
+        if self.items.contains_key(&fresh.id) {
+            self.items.insert(fresh.id.clone(), fresh.clone());
+            self.save_cache()?;
+        }
+
+        Ok(fresh)
     }
 
-    async fn fetch_home_sections(&mut self) -> Result<()> {
+    // ? returns the sections that couldn't be fetched, which are left empty
+    pub async fn fetch_home_sections(&mut self) -> Result<Vec<String>> {
         let user_id = self.auth.clone().unwrap().user.id;
 
         self.continue_watching = self
             .request(
                 self.client
-                    .get(format!(
-                        "{}/Users/{}/Items/Resume",
-                        self.config.server_url, user_id
-                    ))
+                    .get(
+                        self.config
+                            .endpoint(&format!("/Users/{}/Items/Resume", user_id)),
+                    )
                     .query(&[
                         ("Limit", "12"),
-                        (
-                            "Fields",
-                            "Path,Overview,CommunityRating,CriticRating,RunTimeTicks",
-                        ),
+                        ("Fields", HOME_FIELDS),
+                        ("EnableUserData", "true"),
                     ]),
             )
             .await?
@@ -343,14 +1519,12 @@ impl Jellyfin {
         self.next_up = self
             .request(
                 self.client
-                    .get(format!("{}/Shows/NextUp", self.config.server_url))
+                    .get(self.config.endpoint("/Shows/NextUp"))
                     .query(&[
                         ("UserId", user_id.as_str()),
                         ("Limit", "12"),
-                        (
-                            "Fields",
-                            "Path,Overview,CommunityRating,CriticRating,RunTimeTicks",
-                        ),
+                        ("Fields", HOME_FIELDS),
+                        ("EnableUserData", "true"),
                     ]),
             )
             .await?
@@ -361,20 +1535,115 @@ impl Jellyfin {
         self.latest_added = self
             .request(
                 self.client
-                    .get(format!(
-                        "{}/Users/{}/Items",
-                        self.config.server_url, user_id
-                    ))
+                    .get(self.config.endpoint(&format!("/Users/{}/Items", user_id)))
+                    .query(&[
+                        ("Limit", "12"),
+                        ("Fields", HOME_FIELDS),
+                        ("EnableUserData", "true"),
+                        ("IncludeItemTypes", "Movie,Series"),
+                        ("SortBy", self.config.latest_added_sort.sort_by()),
+                        ("SortOrder", "Descending"),
+                        ("Recursive", "true"),
+                    ]),
+            )
+            .await?
+            .json::<JellyfinItemsResponse>()
+            .await?
+            .items;
+
+        for items in [
+            &mut self.continue_watching,
+            &mut self.next_up,
+            &mut self.latest_added,
+        ] {
+            backfill_series_context(items, &self.items);
+        }
+
+        // ? the other home pages are still worth showing without these
+        let mut failed = Vec::new();
+        match self.fetch_playlists().await {
+            Ok(playlists) => failed.extend(playlists),
+            Err(e) => failed.push(format!("playlists: {}", e)),
+        }
+        match self.fetch_libraries().await {
+            Ok(libraries) => failed.extend(libraries),
+            Err(e) => failed.push(format!("libraries: {}", e)),
+        }
+
+        Ok(failed)
+    }
+
+    // ? the latest items of each library, for when latest added is narrowed down to one of them
+    async fn fetch_libraries(&mut self) -> Result<Vec<String>> {
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        self.libraries = self
+            .request(
+                self.client
+                    .get(self.config.endpoint(&format!("/Users/{}/Views", user_id))),
+            )
+            .await?
+            .json::<JellyfinItemsResponse>()
+            .await?
+            .items
+            .into_iter()
+            .filter(|library| {
+                !matches!(
+                    library.collection_type.as_deref(),
+                    Some("playlists" | "livetv" | "boxsets")
+                )
+            })
+            .collect();
+
+        // ? the same query as latest added, so a library is sorted the same way
+        let requests = self
+            .libraries
+            .iter()
+            .map(|library| {
+                self.client
+                    .get(self.config.endpoint(&format!("/Users/{}/Items", user_id)))
                     .query(&[
+                        ("ParentId", library.id.as_str()),
                         ("Limit", "12"),
-                        (
-                            "Fields",
-                            "Path,Overview,CommunityRating,CriticRating,RunTimeTicks",
-                        ),
+                        ("Fields", HOME_FIELDS),
+                        ("EnableUserData", "true"),
                         ("IncludeItemTypes", "Movie,Series"),
-                        ("SortBy", "DateCreated,SortName"),
+                        ("SortBy", self.config.latest_added_sort.sort_by()),
                         ("SortOrder", "Descending"),
                         ("Recursive", "true"),
+                    ])
+            })
+            .collect();
+
+        let mut failed = Vec::new();
+        self.library_latest.clear();
+        for (library, response) in self
+            .libraries
+            .clone()
+            .iter()
+            .zip(self.request_all(requests).await)
+        {
+            let mut latest = items_or_empty(response, &library.name, &mut failed).await;
+
+            backfill_series_context(&mut latest, &self.items);
+            self.library_latest.insert(library.id.clone(), latest);
+        }
+
+        Ok(failed)
+    }
+
+    // ? playlists are small enough to fetch with their items up front, like the other home pages
+    async fn fetch_playlists(&mut self) -> Result<Vec<String>> {
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        self.playlists = self
+            .request(
+                self.client
+                    .get(self.config.endpoint(&format!("/Users/{}/Items", user_id)))
+                    .query(&[
+                        ("IncludeItemTypes", "Playlist"),
+                        ("SortBy", "SortName"),
+                        ("Recursive", "true"),
                     ]),
             )
             .await?
@@ -382,6 +1651,224 @@ impl Jellyfin {
             .await?
             .items;
 
+        let requests = self
+            .playlists
+            .iter()
+            .map(|playlist| self.playlist_items_request(&playlist.id, &user_id))
+            .collect();
+
+        let mut failed = Vec::new();
+        self.playlist_items.clear();
+        for (playlist, response) in self
+            .playlists
+            .clone()
+            .iter()
+            .zip(self.request_all(requests).await)
+        {
+            self.playlist_items.insert(
+                playlist.id.clone(),
+                items_or_empty(response, &playlist.name, &mut failed).await,
+            );
+        }
+
+        Ok(failed)
+    }
+
+    fn playlist_items_request(&self, playlist_id: &str, user_id: &str) -> RequestBuilder {
+        self.client
+            .get(
+                self.config
+                    .endpoint(&format!("/Playlists/{}/Items", playlist_id)),
+            )
+            .query(&[
+                ("UserId", user_id),
+                ("Fields", ITEM_FIELDS),
+                ("EnableUserData", "true"),
+            ])
+    }
+
+    async fn fetch_playlist_items(&mut self, playlist_id: &str) -> Result<()> {
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        let items = self
+            .request(self.playlist_items_request(playlist_id, &user_id))
+            .await?
+            .json::<JellyfinItemsResponse>()
+            .await?
+            .items;
+
+        self.playlist_items.insert(playlist_id.to_string(), items);
+
+        Ok(())
+    }
+
+    pub(crate) async fn add_to_playlist(
+        &mut self,
+        playlist: &MediaItem,
+        items: &[MediaItem],
+    ) -> Result<()> {
+        let user_id = self.auth.clone().unwrap().user.id;
+        let ids = items.iter().map(|item| item.id.as_str()).join(",");
+
+        self.request(
+            self.client
+                .post(
+                    self.config
+                        .endpoint(&format!("/Playlists/{}/Items", playlist.id)),
+                )
+                .query(&[("Ids", ids.as_str()), ("UserId", user_id.as_str())]),
+        )
+        .await?
+        .error_for_status()?;
+
+        self.fetch_playlist_items(&playlist.id).await
+    }
+
+    // ? deleting is opt-in on top of the server permission, so a stray key press can't remove media
+    pub fn can_delete(&self) -> bool {
+        self.config.allow_delete
+            && self
+                .auth
+                .as_ref()
+                .is_some_and(|auth| auth.user.policy.enable_content_deletion)
+    }
+
+    pub(crate) async fn delete_item(&mut self, item: &MediaItem) -> Result<()> {
+        self.request(
+            self.client
+                .delete(self.config.endpoint(&format!("/Items/{}", item.id))),
+        )
+        .await?
+        .error_for_status()?;
+
+        // ? the server also deletes everything inside a series or album
+        let removed = |other: &MediaItem| {
+            other.id == item.id
+                || other.series_id.as_deref() == Some(&item.id)
+                || other.album_id.as_deref() == Some(&item.id)
+        };
+
+        self.items.retain(|_, other| !removed(other));
+        self.continue_watching.retain(|other| !removed(other));
+        self.next_up.retain(|other| !removed(other));
+        self.latest_added.retain(|other| !removed(other));
+
+        self.save_cache()
+    }
+
+    pub(crate) async fn mark_played(&mut self, item: &MediaItem) -> Result<()> {
+        let url = self.config.endpoint(
+            &self
+                .config
+                .server_type
+                .played_item_path(&self.auth.as_ref().unwrap().user.id, &item.id),
+        );
+
+        let user_data = self
+            .request(self.client.post(url))
+            .await?
+            .error_for_status()?
+            .json::<UserData>()
+            .await?;
+
+        if let Some(cached) = self.items.get_mut(&item.id) {
+            cached.user_data = Some(user_data);
+        }
+
+        self.continue_watching.retain(|other| other.id != item.id);
+
+        Ok(())
+    }
+
+    // ? puts back the watched state of an earlier copy of the item. unlike unmarking it as played,
+    // ? this keeps where it was stopped and when it was last played
+    pub(crate) async fn restore_played(&mut self, item: &MediaItem) -> Result<()> {
+        let previous = item.user_data.clone().unwrap_or_default();
+
+        let url = self.config.endpoint(
+            &self
+                .config
+                .server_type
+                .user_data_path(&self.auth.as_ref().unwrap().user.id, &item.id),
+        );
+
+        self.request(self.client.post(url).json(&serde_json::json!({
+            "Played": previous.played,
+            "PlaybackPositionTicks": previous.playback_position_ticks,
+            "LastPlayedDate": previous.last_played_date,
+        })))
+        .await?
+        .error_for_status()?;
+
+        // ? favorites may have changed since, those are kept
+        if let Some(user_data) = self
+            .items
+            .get_mut(&item.id)
+            .and_then(|cached| cached.user_data.as_mut())
+        {
+            user_data.played = previous.played;
+            user_data.playback_position_ticks = previous.playback_position_ticks;
+            user_data.last_played_date = previous.last_played_date;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) async fn set_favorite(&mut self, item: &MediaItem, favorite: bool) -> Result<()> {
+        let url = self.config.endpoint(
+            &self
+                .config
+                .server_type
+                .favorite_item_path(&self.auth.as_ref().unwrap().user.id, &item.id),
+        );
+
+        let user_data = self
+            .request(if favorite {
+                self.client.post(url)
+            } else {
+                self.client.delete(url)
+            })
+            .await?
+            .error_for_status()?
+            .json::<UserData>()
+            .await?;
+
+        if let Some(cached) = self.items.get_mut(&item.id) {
+            cached.user_data = Some(user_data);
+        }
+
+        Ok(())
+    }
+
+    // ? playback on a copy only changes the watched state of what it played, the rest of this one stays as is
+    pub(crate) fn take_user_data(&mut self, other: &Jellyfin, items: &[MediaItem]) -> Result<()> {
+        for item in items {
+            let user_data = other
+                .items
+                .get(&item.id)
+                .map(|other| other.user_data.clone());
+
+            if let (Some(cached), Some(user_data)) = (self.items.get_mut(&item.id), user_data) {
+                cached.user_data = user_data;
+            }
+        }
+
+        self.continue_watching
+            .retain(|item| !items.contains(item) || other.continue_watching.contains(item));
+
+        self.save_cache()
+    }
+
+    // ? written next to the cache and renamed over it, so being killed mid-write can't leave a truncated cache
+    pub(crate) fn save_cache(&self) -> Result<()> {
+        let temp_path = self.cache_path.with_extension("tmp");
+
+        fs::write(
+            &temp_path,
+            encode_cache(self.config.cache_format, &self.items)?,
+        )?;
+        fs::rename(&temp_path, &self.cache_path)?;
+
         Ok(())
     }
 
@@ -407,26 +1894,226 @@ impl Jellyfin {
         episodes
     }
 
-    pub async fn play_media(&mut self, item: &MediaItem) -> Result<Option<MediaItem>> {
+    pub fn get_unwatched_episodes_from_series(&self, series_id: &str) -> Vec<MediaItem> {
+        self.get_episodes_from_series(series_id)
+            .into_iter()
+            .filter(|episode| !episode.user_data.as_ref().is_some_and(|data| data.played))
+            .collect()
+    }
+
+    pub fn get_albums_from_artist(&self, artist_id: &str) -> Vec<MediaItem> {
+        let mut albums: Vec<_> = self
+            .items
+            .values()
+            .filter(|item| {
+                item.type_ == "MusicAlbum"
+                    && item
+                        .album_artists
+                        .iter()
+                        .any(|artist| artist.id == artist_id)
+            })
+            .cloned()
+            .collect();
+
+        albums.sort_by(|a, b| (a.year, &a.name).cmp(&(b.year, &b.name)));
+
+        albums
+    }
+
+    pub fn get_tracks_from_album(&self, album_id: &str) -> Vec<MediaItem> {
+        let mut tracks: Vec<_> = self
+            .items
+            .values()
+            .filter(|item| item.type_ == "Audio" && item.album_id.as_deref() == Some(album_id))
+            .cloned()
+            .collect();
+
+        // ? parent index number is the disc number for tracks
+        tracks.sort_by(|a, b| {
+            (
+                a.parent_index_number.unwrap_or(0),
+                a.index_number.unwrap_or(0),
+            )
+                .cmp(&(
+                    b.parent_index_number.unwrap_or(0),
+                    b.index_number.unwrap_or(0),
+                ))
+        });
+
+        tracks
+    }
+
+    pub(crate) async fn play_media(
+        &mut self,
+        item: &MediaItem,
+        source: &MediaSource,
+        start_ticks: Option<i64>,
+    ) -> Result<Option<MediaItem>> {
+        let runtime_ticks = source.runtime_ticks;
+
+        let skip_ranges = self.fetch_skip_ranges_for(std::slice::from_ref(item)).await;
+
+        let position_ticks = match start_ticks {
+            Some(ticks) => ticks,
+            None => {
+                let position_ticks = self.fetch_position_ticks(item).await?;
+
+                // ? resuming inside an intro starts right after it instead of seeking once playing
+                skip_ranges
+                    .get(&item.id)
+                    .and_then(|ranges| {
+                        ranges
+                            .iter()
+                            .find(|(start, end)| (*start..*end).contains(&position_ticks))
+                    })
+                    .map_or(position_ticks, |(_, end)| *end)
+            }
+        };
+
+        let position_seconds = position_ticks / 10_000_000;
+
+        let runtime_seconds = runtime_ticks / 10_000_000;
+
+        let title = self.media_title(item).await;
+
+        let session = session_id();
+
+        let socket_path = format!("/tmp/mpv-socket-{}-{}", item.id, session);
+
+        let mut command = self.mpv_command(&socket_path);
+        command
+            .arg(self.source_url(item, source))
+            .arg(format!("--length={}", runtime_seconds))
+            .arg(format!("--force-media-title={}", title));
+
+        if item.type_ == "Audio" {
+            command.arg("--no-video");
+        }
+
+        // ? forced subtitles only cover foreign dialogue, so they are shown even when subtitles are off
+        if self.config.forced_subtitles {
+            let language = &self
+                .auth
+                .as_ref()
+                .unwrap()
+                .user
+                .config
+                .subtitle_language_preference;
+
+            if let Some(track) = source.forced_subtitle_track(language) {
+                command.arg(format!("--sid={}", track));
+            }
+        }
+
+        if position_seconds > 0 {
+            command.arg(format!("--start={}", position_seconds));
+        }
+
+        if let Some(path) = self.external_subtitle(item, source).await {
+            command.arg(format!("--sub-file={}", path.display()));
+        }
+
+        self.mark_watched_on_start(item).await;
+
+        self.spawn_and_monitor(
+            command,
+            &session,
+            &socket_path,
+            std::slice::from_ref(item),
+            position_ticks,
+            skip_ranges,
+        )
+        .await
+    }
+
+    pub(crate) async fn play_queue(&mut self, queue: &[MediaItem]) -> Result<Option<MediaItem>> {
+        let Some(first) = queue.first() else {
+            return Ok(None);
+        };
+
+        let position_ticks = self.fetch_position_ticks(first).await?;
+
+        let mut skip_ranges = self.fetch_skip_ranges_for(queue).await;
+
+        // ? --start would apply to every playlist entry, so resume the first one by skipping ahead instead
+        if position_ticks > 0 {
+            skip_ranges
+                .entry(first.id.clone())
+                .or_default()
+                .push((0, position_ticks));
+        }
+
+        let mut playlist = String::from("#EXTM3U\n");
+        for item in queue {
+            playlist.push_str(&format!(
+                "#EXTINF:{},{}\n{}\n",
+                item.runtime_ticks.map_or(-1, |ticks| ticks / 10_000_000),
+                self.media_title(item).await.trim_start(),
+                self.stream_url(item, &item.id)
+            ));
+        }
+
+        let session = session_id();
+
+        let playlist_path = format!("/tmp/jellytui-queue-{}-{}.m3u", first.id, session);
+        fs::write(&playlist_path, playlist)?;
+
+        let socket_path = format!("/tmp/mpv-socket-{}-{}", first.id, session);
+
+        let mut command = self.mpv_command(&socket_path);
+        command.arg(format!("--playlist={}", playlist_path));
+
+        self.mark_watched_on_start(first).await;
+
+        let next = self
+            .spawn_and_monitor(command, &session, &socket_path, queue, 0, skip_ranges)
+            .await;
+
+        fs::remove_file(playlist_path)?;
+
+        next
+    }
+
+    pub async fn fetch_media_sources(&mut self, item: &MediaItem) -> Result<Vec<MediaSource>> {
         let playback_info = self
             .request(
                 self.client
-                    .post(format!(
-                        "{}/Items/{}/PlaybackInfo",
-                        self.config.server_url, item.id
-                    ))
+                    .post(
+                        self.config
+                            .endpoint(&format!("/Items/{}/PlaybackInfo", item.id)),
+                    )
                     .json(&serde_json::json!({
+                        "MaxStreamingBitrate": self.max_streaming_bitrate(),
                         "DeviceProfile": {
-                            "MaxStreamingBitrate": 140000000,
+                            "MaxStreamingBitrate": self.max_streaming_bitrate(),
                             "DirectPlayProfiles": [
                                 {
                                     "Container": "mkv,mp4,avi",
                                     "Type": "Video",
                                     "VideoCodec": "h264,hevc,mpeg4,mpeg2video",
                                     "AudioCodec": "aac,mp3,ac3,eac3,flac,vorbis,opus"
+                                },
+                                {
+                                    "Container": "mp3,flac,ogg,oga,opus,m4a,aac,wav,webma",
+                                    "Type": "Audio"
                                 }
                             ],
-                            "TranscodingProfiles": []
+                            "TranscodingProfiles": [
+                                {
+                                    "Container": "ts",
+                                    "Type": "Video",
+                                    "VideoCodec": "h264",
+                                    "AudioCodec": "aac",
+                                    "Protocol": "hls",
+                                    "Context": "Streaming"
+                                },
+                                {
+                                    "Container": "mp3",
+                                    "Type": "Audio",
+                                    "AudioCodec": "mp3",
+                                    "Context": "Streaming"
+                                }
+                            ]
                         }
                     })),
             )
@@ -434,34 +2121,81 @@ impl Jellyfin {
             .json::<PlaybackInfo>()
             .await?;
 
-        let source = playback_info
-            .media_sources
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("No media source available"))?;
+        if playback_info.media_sources.is_empty() {
+            return Err(anyhow::anyhow!("No media source available"));
+        }
+
+        Ok(playback_info.media_sources)
+    }
 
-        let position_url = format!("{}/UserItems/{}/UserData", self.config.server_url, item.id);
+    async fn fetch_position_ticks(&mut self, item: &MediaItem) -> Result<i64> {
+        let position_url = self
+            .config
+            .endpoint(&format!("/UserItems/{}/UserData", item.id));
 
-        let position_ticks = self
+        Ok(self
             .request(self.client.get(&position_url))
             .await?
             .json::<serde_json::Value>()
             .await?
             .get("PlaybackPositionTicks")
             .and_then(|v| v.as_i64())
-            .unwrap_or(0);
+            .unwrap_or(0))
+    }
 
-        let position_seconds = position_ticks / 10_000_000;
+    // ? the config wins over the limit set for the user on the server, which wins over the old fixed default
+    fn max_streaming_bitrate(&self) -> u64 {
+        self.config
+            .max_streaming_bitrate
+            .or(self
+                .auth
+                .as_ref()
+                .map(|auth| auth.user.policy.remote_client_bitrate_limit)
+                .filter(|limit| *limit > 0))
+            .unwrap_or(140_000_000)
+    }
+
+    fn source_url(&self, item: &MediaItem, source: &MediaSource) -> String {
+        match &source.transcoding_url {
+            Some(url) if source.bitrate.unwrap_or(0) > self.max_streaming_bitrate() => {
+                self.config.endpoint(url)
+            }
+            _ => self.stream_url(item, &source.id),
+        }
+    }
 
-        let runtime_seconds = source.runtime_ticks / 10_000_000;
+    // ? mpv authenticates through a header, anything else needs the token in the url
+    pub async fn shareable_stream_url(&mut self, item: &MediaItem) -> Result<String> {
+        let sources = self.fetch_media_sources(item).await?;
+        let url = self.source_url(item, &sources[0]);
 
-        let auth = self.auth.clone().unwrap();
+        if url.contains("api_key=") {
+            return Ok(url);
+        }
 
-        let stream_url = format!(
-            "{}/Videos/{}/stream?static=true&mediaSourceId={}&tag={}",
-            self.config.server_url, item.id, item.id, auth.access_token
-        );
+        Ok(format!(
+            "{}&api_key={}",
+            url,
+            self.auth.as_ref().unwrap().access_token
+        ))
+    }
+
+    fn stream_url(&self, item: &MediaItem, media_source_id: &str) -> String {
+        self.config.endpoint(&format!(
+            "/{}/{}/stream?static=true&mediaSourceId={}&tag={}",
+            if item.type_ == "Audio" {
+                "Audio"
+            } else {
+                "Videos"
+            },
+            item.id,
+            media_source_id,
+            self.auth.as_ref().unwrap().access_token
+        ))
+    }
 
-        let title = if item.type_ == "Episode" {
+    async fn media_title(&mut self, item: &MediaItem) -> String {
+        if item.type_ == "Episode" {
             format!(
                 "  {} - S{:02}E{:02} - {}",
                 item.series_name.as_deref().unwrap_or("Unknown Series"),
@@ -469,38 +2203,58 @@ impl Jellyfin {
                 item.index_number.unwrap_or(0),
                 item.name
             )
-        } else if let Some(year) = item.year {
-            format!("  {} ({})", item.name, year)
+        } else if item.type_ == "Audio" {
+            format!(
+                "  {} - {} - {}",
+                item.artists.join(", "),
+                item.album.as_deref().unwrap_or("Unknown Album"),
+                item.name
+            )
         } else {
-            format!("  {}", item.name)
-        };
+            let mut title = match item.year {
+                Some(year) => format!("  {} ({})", item.name, year),
+                None => format!("  {}", item.name),
+            };
+
+            if let Some(collection) = self.fetch_collection_name(item).await {
+                title.push_str(&format!(" — {}", collection));
+            }
 
-        let socket_path = format!("/tmp/mpv-socket-{}", item.id);
+            title
+        }
+    }
+
+    fn mpv_command(&self, socket_path: &str) -> Command {
+        let auth = self.auth.clone().unwrap();
 
-        let mut command = Command::new("mpv");
+        let mut command = Command::new(self.config.player());
         command
-            .arg(stream_url)
             .arg("--no-cache-pause")
-            .arg(format!("--demuxer-lavf-probe-info=yes"))
-            .arg(format!("--demuxer-lavf-analyzeduration=10"))
-            .arg(format!("--length={}", runtime_seconds))
-            .arg(format!("--force-media-title={}", title))
+            .arg("--demuxer-lavf-probe-info=yes")
+            .arg("--demuxer-lavf-analyzeduration=10")
             .arg(format!(
-                "--http-header-fields=X-MediaBrowser-Token: {}",
+                "--http-header-fields={}: {}",
+                self.config.server_type.token_header(),
                 auth.access_token
             ))
             .arg(format!("--input-ipc-server={}", socket_path));
 
-        if !auth.user.config.play_default_audio_track
-            && auth.user.config.audio_language_preference.is_some()
+        // ? languages from the config win over the server's single preference, mpv takes the first one available
+        if let Some(languages) = self.config.audio_languages() {
+            command.arg(format!("--alang={}", languages));
+        } else if let Some(audio_language_preference) = auth
+            .user
+            .config
+            .audio_language_preference
+            .filter(|_| !auth.user.config.play_default_audio_track)
         {
-            command.arg(format!(
-                "--alang={}",
-                auth.user.config.audio_language_preference.unwrap()
-            ));
+            command.arg(format!("--alang={}", audio_language_preference));
         }
 
-        if auth.user.config.subtitle_language_preference == "none" {
+        if let Some(languages) = self.config.subtitle_languages() {
+            command.arg(format!("--slang={}", languages));
+            command.arg("--sub-auto=fuzzy");
+        } else if auth.user.config.subtitle_language_preference == "none" {
             command.arg("--no-sub");
         } else {
             command.arg(format!(
@@ -511,66 +2265,156 @@ impl Jellyfin {
             command.arg("--sub-auto=fuzzy");
         }
 
-        if position_seconds > 0 {
-            command.arg(format!("--start={}", position_seconds));
+        if let Some(visible) = self.config.subtitle_visibility {
+            command.arg(format!(
+                "--sub-visibility={}",
+                if visible { "yes" } else { "no" }
+            ));
         }
 
+        command.args(self.config.mpv_args());
+
+        command
+    }
+
+    async fn spawn_and_monitor(
+        &mut self,
+        mut command: Command,
+        session: &str,
+        socket_path: &String,
+        queue: &[MediaItem],
+        start_position: i64,
+        skip_ranges: HashMap<String, Vec<(i64, i64)>>,
+    ) -> Result<Option<MediaItem>> {
+        // ? the player's errors are kept, so a failed playback can say what went wrong. one log per
+        // ? session, as players can run side by side
+        let log_path = self
+            .cache_path
+            .with_file_name(format!("mpv-{}.log", session));
+        let log = fs::File::create(&log_path)?;
+
         let child = command
             .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()?;
+            .stderr(log)
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    anyhow::Error::new(PlayerNotFound(self.config.player().to_string()))
+                }
+                _ => e.into(),
+            })?;
 
-        self.mpv_processes.lock().unwrap().push(child);
+        self.mpv_sessions
+            .lock()
+            .unwrap()
+            .insert(socket_path.clone(), child);
 
-        // wait for mpv to start
-        std::thread::sleep(Duration::from_secs(2));
+        *self.mpv_socket.lock().unwrap() = Some(socket_path.clone());
 
-        let next = self.monitor_playback(item, &socket_path).await;
+        let next = self
+            .monitor_playback(queue, socket_path, start_position, skip_ranges)
+            .await;
+
+        // ? another player may have been started in the meantime, which keeps remote control
+        {
+            let mut remote = self.mpv_socket.lock().unwrap();
+            if remote.as_ref() == Some(socket_path) {
+                *remote = None;
+            }
+        }
 
-        std::fs::remove_file(socket_path)?;
+        // ? the socket closes just before the player exits, so give it a moment to report back
+        let mut status = None;
+        for _ in 0..10 {
+            status = self
+                .mpv_sessions
+                .lock()
+                .unwrap()
+                .get_mut(socket_path)
+                .and_then(|child| child.try_wait().ok().flatten());
+            if status.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
 
-        next
+        // ? a player that is somehow still running stays tracked, so cleanup can still stop it
+        let Some(status) = status else {
+            return next;
+        };
+        self.mpv_sessions.lock().unwrap().remove(socket_path);
+        remove_socket(socket_path)?;
+
+        // ? a player stopped by a signal was stopped on purpose, only its own errors are reported
+        match status.code() {
+            Some(code) if code != 0 => Err(PlayerFailed {
+                player: self.config.player().to_string(),
+                code,
+                output: last_lines(&fs::read_to_string(&log_path).unwrap_or_default(), 3),
+                log_path,
+            }
+            .into()),
+            _ => {
+                let _ = fs::remove_file(&log_path);
+                next
+            }
+        }
     }
 
     async fn monitor_playback(
         &mut self,
-        item: &MediaItem,
+        queue: &[MediaItem],
         socket_path: &String,
+        start_position: i64,
+        mut skip_ranges: HashMap<String, Vec<(i64, i64)>>,
     ) -> Result<Option<MediaItem>> {
-        let mut last_position = 0i64;
+        let mut current = 0;
+        // ? progress reports are throttled, stopping reports where playback actually was
+        let mut position = start_position;
+        let mut last_position = start_position;
         let mut last_update = std::time::Instant::now();
 
         let timeout = Duration::from_secs(10);
         let retry_delay = Duration::from_millis(50);
 
-        let mut socket = loop {
-            match UnixStream::connect(&socket_path) {
+        let socket = loop {
+            match UnixStream::connect(socket_path).await {
                 Ok(socket) => break socket,
                 Err(_) => {
-                    if last_update.elapsed() >= timeout {
+                    // ? a player that died on startup will never create it
+                    let exited = self
+                        .mpv_sessions
+                        .lock()
+                        .unwrap()
+                        .get_mut(socket_path)
+                        .is_some_and(|child| matches!(child.try_wait(), Ok(Some(_))));
+                    if exited || last_update.elapsed() >= timeout {
                         return Ok(None);
                     }
-                    std::thread::sleep(retry_delay);
+                    tokio::time::sleep(retry_delay).await;
                 }
             }
         };
 
-        if let Err(e) = socket.write_all(
-            b"{\"command\":[\"observe_property\",1,\"playback-time\"]}\n\
-            {\"command\":[\"observe_property\",2,\"pause\"]}\n\
-            {\"command\":[\"observe_property\",3,\"eof-reached\"]}\n",
-        ) {
+        let (reader, mut socket) = socket.into_split();
+
+        if let Err(e) = socket
+            .write_all(
+                b"{\"command\":[\"observe_property\",1,\"playback-time\"]}\n\
+                {\"command\":[\"observe_property\",2,\"pause\"]}\n\
+                {\"command\":[\"observe_property\",3,\"eof-reached\"]}\n\
+                {\"command\":[\"observe_property\",4,\"playlist-pos\"]}\n",
+            )
+            .await
+        {
             eprintln!("Failed to write to socket: {}", e);
             return Ok(None);
         }
 
-        let mut buffer = [0u8; 1024];
-        while let Ok(n) = socket.read(&mut buffer) {
-            if n == 0 {
-                break;
-            }
+        let mut lines = BufReader::new(reader).lines();
 
-            let Ok(response) = serde_json::from_slice::<serde_json::Value>(&buffer[..n]) else {
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Ok(response) = serde_json::from_str::<serde_json::Value>(&line) else {
                 continue;
             };
 
@@ -578,6 +2422,8 @@ impl Jellyfin {
                 continue;
             };
 
+            let item = &queue[current];
+
             match event.as_str().unwrap() {
                 "property-change" => {
                     let Some(name) = response.get("name") else {
@@ -597,13 +2443,10 @@ impl Jellyfin {
                             if let Err(e) = self
                                 .request(
                                     self.client
-                                        .post(format!(
-                                            "{}/Sessions/Playing/Progress",
-                                            self.config.server_url
-                                        ))
+                                        .post(self.config.endpoint("/Sessions/Playing/Progress"))
                                         .json(&serde_json::json!({
                                             "ItemId": item.id,
-                                            "PositionTicks": last_position,
+                                            "PositionTicks": position,
                                             "IsPaused": paused
                                         })),
                                 )
@@ -611,20 +2454,76 @@ impl Jellyfin {
                             {
                                 eprintln!("Failed to update pause state: {}", e);
                             }
+
+                            self.scrobble(if paused { "pause" } else { "start" }, item, position)
+                                .await;
+                        }
+                        "playlist-pos" => {
+                            let Some(playlist_position) = response
+                                .get("data")
+                                .and_then(|data| data.as_u64())
+                                .map(|position| position as usize)
+                                .filter(|position| *position < queue.len())
+                            else {
+                                continue;
+                            };
+
+                            if playlist_position == current {
+                                continue;
+                            }
+
+                            self.report_stopped(item, position).await;
+
+                            current = playlist_position;
+                            position = 0;
+                            last_position = 0;
+                            last_update = std::time::Instant::now();
+
+                            self.scrobble("start", &queue[current], 0).await;
+
+                            self.mark_watched_on_start(&queue[current]).await;
                         }
                         "playback-time" => {
                             let Some(data) = response.get("data") else {
                                 continue;
                             };
 
-                            let Some(position) = data.as_f64() else {
+                            let Some(position_secs) = data.as_f64() else {
                                 continue;
                             };
 
-                            let position_ticks = (position * 10_000_000.0) as i64;
+                            let position_ticks = (position_secs * 10_000_000.0) as i64;
+                            position = position_ticks;
+
+                            if let Some(ranges) = skip_ranges.get_mut(&item.id) {
+                                if let Some(index) = ranges.iter().position(|(start, end)| {
+                                    (*start..*end).contains(&position_ticks)
+                                }) {
+                                    // each range is only skipped once, so seeking back into it is respected
+                                    let (_, end) = ranges.remove(index);
+
+                                    if let Err(e) = socket
+                                        .write_all(
+                                            format!(
+                                                "{{\"command\":[\"seek\",{},\"absolute\"]}}\n",
+                                                end as f64 / 10_000_000.0
+                                            )
+                                            .as_bytes(),
+                                        )
+                                        .await
+                                    {
+                                        eprintln!("Failed to skip intro: {}", e);
+                                    }
+
+                                    continue;
+                                }
+                            }
 
-                            if (position_ticks - last_position).abs() < 50_000_000
-                                || last_update.elapsed() < Duration::from_secs(10)
+                            // ? also needs half an interval of movement, so nothing is reported while paused
+                            let interval = self.config.progress_report_interval();
+                            if (position_ticks - last_position).abs()
+                                < interval.as_millis() as i64 * 10_000 / 2
+                                || last_update.elapsed() < interval
                             {
                                 continue;
                             }
@@ -632,10 +2531,7 @@ impl Jellyfin {
                             if let Err(e) = self
                                 .request(
                                     self.client
-                                        .post(format!(
-                                            "{}/Sessions/Playing/Progress",
-                                            self.config.server_url
-                                        ))
+                                        .post(self.config.endpoint("/Sessions/Playing/Progress"))
                                         .json(&serde_json::json!({
                                             "ItemId": item.id,
                                             "PositionTicks": position_ticks
@@ -652,35 +2548,49 @@ impl Jellyfin {
                         _ => {}
                     }
                 }
-                "end-file" => {
-                    if response.get("reason") == Some(&serde_json::Value::String("eof".to_string()))
-                    {
+                // ? within a queue mpv moves on by itself, which is reported through playlist-pos
+                "end-file"
+                    if current + 1 == queue.len()
+                        && response.get("reason")
+                            == Some(&serde_json::Value::String("eof".to_string())) =>
+                {
+                    self.report_stopped(item, item.runtime_ticks.unwrap_or(0))
+                        .await;
+
+                    if item.type_ == "Audio" {
                         return Ok(self
-                            .get_episodes_from_series(item.series_id.as_deref().unwrap())
-                            .iter()
-                            .find(|ep| {
-                                ep.index_number == item.index_number.map(|i| i + 1)
-                                    || ep.parent_index_number
-                                        == item.parent_index_number.map(|i| i + 1)
-                                        && ep.index_number == Some(1)
-                            })
-                            .cloned());
+                            .get_tracks_from_album(item.album_id.as_deref().unwrap_or_default())
+                            .into_iter()
+                            .skip_while(|track| track.id != item.id)
+                            .nth(1));
                     }
+
+                    let Some(series_id) = item.series_id.as_deref() else {
+                        return Ok(None);
+                    };
+
+                    return Ok(next_episode(
+                        &self.get_episodes_from_series(series_id),
+                        item,
+                    ));
                 }
                 _ => {}
             }
         }
 
+        self.report_stopped(&queue[current], position).await;
+
+        Ok(None)
+    }
+
+    async fn report_stopped(&mut self, item: &MediaItem, position_ticks: i64) {
         if let Err(e) = self
             .request(
                 self.client
-                    .post(format!(
-                        "{}/Sessions/Playing/Stopped",
-                        self.config.server_url
-                    ))
+                    .post(self.config.endpoint("/Sessions/Playing/Stopped"))
                     .json(&serde_json::json!({
                         "ItemId": item.id,
-                        "PositionTicks": last_position
+                        "PositionTicks": position_ticks
                     })),
             )
             .await
@@ -688,30 +2598,608 @@ impl Jellyfin {
             eprintln!("Failed to update progress: {}", e);
         }
 
-        return Ok(None);
+        self.scrobble("stop", item, position_ticks).await;
+
+        // ? so resuming from the app starts where the player stopped, without refetching
+        if let Some(user_data) = self
+            .items
+            .get_mut(&item.id)
+            .and_then(|cached| cached.user_data.as_mut())
+        {
+            user_data.playback_position_ticks = position_ticks;
+        }
+
+        let Some(runtime_ticks) = item.runtime_ticks.filter(|ticks| *ticks > 0) else {
+            return;
+        };
+
+        let Some(watched_percent) = self.config.stop_watched_percent() else {
+            return;
+        };
+
+        if position_ticks * 100 >= runtime_ticks * watched_percent as i64 {
+            if let Err(e) = self.mark_played(item).await {
+                eprintln!("Failed to mark as watched: {}", e);
+            }
+        }
+    }
+
+    // ? only for media without subtitles of its own, each item's are downloaded once and kept with the cache
+    async fn external_subtitle(&self, item: &MediaItem, source: &MediaSource) -> Option<PathBuf> {
+        let opensubtitles = self.opensubtitles.as_ref()?;
+
+        if source.has_embedded_subtitles() || item.type_ == "Audio" {
+            return None;
+        }
+
+        let imdb_id = item.imdb_id()?;
+
+        let path = self
+            .cache_path
+            .with_file_name("subtitles")
+            .join(format!("{}.srt", item.id));
+
+        if !path.exists() {
+            if let Err(e) = opensubtitles.download(imdb_id, &path).await {
+                eprintln!("Failed to download subtitles: {}", e);
+                return None;
+            }
+        }
+
+        Some(path)
+    }
+
+    async fn mark_watched_on_start(&mut self, item: &MediaItem) {
+        if self.config.mark_watched != MarkWatched::Start {
+            return;
+        }
+
+        if let Err(e) = self.mark_played(item).await {
+            eprintln!("Failed to mark as watched: {}", e);
+        }
+    }
+
+    async fn fetch_collection_name(&mut self, item: &MediaItem) -> Option<String> {
+        if self.collections.is_none() {
+            self.collections = Some(self.fetch_collections().await.unwrap_or_default());
+        }
+
+        self.collections.as_ref()?.get(&item.id).cloned()
+    }
+
+    // ? the Ancestors endpoint leaves out BoxSets, so collections are looked up from the other side
+    async fn fetch_collections(&mut self) -> Result<HashMap<String, String>> {
+        let user_id = self.auth.clone().unwrap().user.id;
+        let endpoint = self.config.endpoint(&format!("/Users/{}/Items", user_id));
+
+        let box_sets = self
+            .request(
+                self.client
+                    .get(&endpoint)
+                    .query(&[("IncludeItemTypes", "BoxSet"), ("Recursive", "true")]),
+            )
+            .await?
+            .json::<JellyfinItemsResponse>()
+            .await?
+            .items;
+
+        let requests = box_sets
+            .iter()
+            .map(|box_set| {
+                self.client
+                    .get(&endpoint)
+                    .query(&[("ParentId", box_set.id.as_str())])
+            })
+            .collect();
+
+        let mut collections = HashMap::new();
+        for (box_set, response) in box_sets.iter().zip(self.request_all(requests).await) {
+            for child in response_items(response).await.unwrap_or_default() {
+                collections.insert(child.id, box_set.name.clone());
+            }
+        }
+
+        Ok(collections)
+    }
+
+    async fn fetch_skip_ranges_for(
+        &mut self,
+        items: &[MediaItem],
+    ) -> HashMap<String, Vec<(i64, i64)>> {
+        let mut skip_ranges = HashMap::new();
+
+        if !self.config.skip_intros {
+            return skip_ranges;
+        }
+
+        for item in items {
+            skip_ranges.insert(item.id.clone(), self.fetch_skip_ranges(item).await);
+        }
+
+        skip_ranges
     }
 
-    pub async fn refresh_cache(&mut self) -> Result<()> {
+    async fn fetch_skip_ranges(&mut self, item: &MediaItem) -> Vec<(i64, i64)> {
+        // prefer media segments (e.g. from the intro skipper plugin) when the server has them, emby never does
+        if self.config.server_type == ServerType::Jellyfin {
+            let ranges = self.fetch_segment_ranges(item).await;
+
+            if !ranges.is_empty() {
+                return ranges;
+            }
+        }
+
+        let user_id = self.auth.clone().unwrap().user.id;
+
+        let Ok(response) = self
+            .request(
+                self.client.get(
+                    self.config
+                        .endpoint(&format!("/Users/{}/Items/{}", user_id, item.id)),
+                ),
+            )
+            .await
+        else {
+            return Vec::new();
+        };
+
+        let Ok(details) = response.json::<MediaItem>().await else {
+            return Vec::new();
+        };
+
+        details
+            .chapters
+            .iter()
+            .enumerate()
+            .filter(|(_, chapter)| {
+                chapter.name.as_deref().is_some_and(|name| {
+                    let name = name.to_lowercase();
+                    name.contains("intro") || name.contains("recap")
+                })
+            })
+            .map(|(index, chapter)| {
+                let end = details
+                    .chapters
+                    .get(index + 1)
+                    .map(|next| next.start_position_ticks)
+                    .or(details.runtime_ticks)
+                    .unwrap_or(chapter.start_position_ticks);
+
+                (chapter.start_position_ticks, end)
+            })
+            .collect()
+    }
+
+    async fn fetch_segment_ranges(&mut self, item: &MediaItem) -> Vec<(i64, i64)> {
+        let Ok(response) = self
+            .request(
+                self.client
+                    .get(self.config.endpoint(&format!("/MediaSegments/{}", item.id)))
+                    .query(&[("includeSegmentTypes", "Intro,Recap")]),
+            )
+            .await
+        else {
+            return Vec::new();
+        };
+
+        let Ok(segments) = response.json::<MediaSegmentsResponse>().await else {
+            return Vec::new();
+        };
+
+        segments
+            .items
+            .iter()
+            .filter(|segment| matches!(segment.type_.as_str(), "Intro" | "Recap"))
+            .map(|segment| (segment.start_ticks, segment.end_ticks))
+            .collect()
+    }
+
+    async fn scrobble(&self, action: &str, item: &MediaItem, position_ticks: i64) {
+        let Some(trakt) = &self.trakt else {
+            return;
+        };
+
+        let Some(runtime_ticks) = item.runtime_ticks.filter(|ticks| *ticks > 0) else {
+            return;
+        };
+
+        let series = item
+            .series_id
+            .as_ref()
+            .and_then(|series_id| self.items.get(series_id));
+
+        let progress = position_ticks as f64 / runtime_ticks as f64 * 100.0;
+
+        if let Err(e) = trakt.scrobble(action, item, series, progress).await {
+            eprintln!("Failed to scrobble to trakt: {}", e);
+        }
+    }
+
+    pub(crate) async fn refresh_cache(&mut self) -> Result<RefreshSummary> {
+        let previous: HashSet<String> = self.items.keys().cloned().collect();
+
         fs::remove_file(&self.cache_path)?;
 
         self.fetch_all_media().await?;
-        self.fetch_home_sections().await?;
+        let failed = self.fetch_home_sections().await?;
+
+        Ok(RefreshSummary {
+            total: self.items.len(),
+            new: self
+                .items
+                .keys()
+                .filter(|id| !previous.contains(*id))
+                .count(),
+            updated_at: chrono::Local::now(),
+            failed,
+        })
+    }
+
+    // ? trailers are usually on youtube, which mpv plays through yt-dlp. nothing is reported to the
+    // ? server for them, so the player just runs next to the app until it is closed or stopped with cleanup
+    pub(crate) fn play_trailer(&self, item: &MediaItem) -> Result<()> {
+        let url = item
+            .trailer_url()
+            .ok_or_else(|| anyhow::anyhow!("No trailer available for {}", item.name))?;
+
+        let socket_path = format!("/tmp/mpv-socket-trailer-{}-{}", item.id, session_id());
+
+        let child = Command::new(self.config.player())
+            .arg("--no-terminal")
+            .arg(format!("--force-media-title={} (Trailer)", item.name))
+            .arg(format!("--input-ipc-server={}", socket_path))
+            .args(self.config.mpv_args())
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => {
+                    anyhow::Error::new(PlayerNotFound(self.config.player().to_string()))
+                }
+                _ => e.into(),
+            })?;
+
+        self.mpv_sessions.lock().unwrap().insert(socket_path, child);
 
         Ok(())
     }
 
-    pub fn cleanup(&self) -> Result<()> {
-        let Ok(mut processes) = self.mpv_processes.lock() else {
+    pub(crate) fn stop_playback(&self) -> Result<()> {
+        self.playback_control().stop()
+    }
+
+    pub(crate) fn playback_control(&self) -> PlaybackControl {
+        PlaybackControl {
+            mpv_sessions: self.mpv_sessions.clone(),
+            mpv_socket: self.mpv_socket.clone(),
+        }
+    }
+
+    pub(crate) fn cleanup(&self) -> Result<()> {
+        let Ok(mut sessions) = self.mpv_sessions.lock() else {
             return Ok(());
         };
 
-        for process in processes.iter_mut() {
+        for (socket_path, mut process) in sessions.drain() {
             process.kill()?;
             process.wait()?;
+            remove_socket(&socket_path)?;
         }
 
-        processes.clear();
-
         Ok(())
     }
 }
+
+fn session_id() -> String {
+    format!(
+        "{}-{}",
+        std::process::id(),
+        NEXT_SESSION.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// ? mpv leaves its socket behind, unless it never got as far as creating it
+fn remove_socket(socket_path: &str) -> Result<()> {
+    match fs::remove_file(socket_path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(season: i64, index: i64) -> MediaItem {
+        MediaItem {
+            id: format!("s{}e{}", season, index),
+            type_: "Episode".to_string(),
+            parent_index_number: Some(season),
+            index_number: Some(index),
+            ..Default::default()
+        }
+    }
+
+    fn series() -> Vec<MediaItem> {
+        vec![
+            episode(1, 1),
+            episode(1, 2),
+            episode(1, 3),
+            episode(1, 4),
+            episode(1, 5),
+            episode(2, 1),
+            episode(2, 2),
+            episode(2, 3),
+            episode(2, 4),
+            episode(2, 5),
+            episode(2, 6),
+        ]
+    }
+
+    #[test]
+    fn media_source_label_includes_resolution_and_size() {
+        let source: MediaSource = serde_json::from_value(serde_json::json!({
+            "Id": "source",
+            "Name": "Director's Cut",
+            "Size": 12_300_000_000_i64,
+            "RunTimeTicks": 0,
+            "MediaStreams": [
+                { "Type": "Audio" },
+                { "Type": "Video", "Width": 3840, "Height": 2160 }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(source.label(), "Director's Cut - 3840x2160 - 12.3 GB");
+    }
+
+    #[test]
+    fn forced_subtitle_track_prefers_language() {
+        let source: MediaSource = serde_json::from_value(serde_json::json!({
+            "Id": "source",
+            "RunTimeTicks": 0,
+            "MediaStreams": [
+                { "Type": "Video", "Index": 0 },
+                { "Type": "Subtitle", "Index": 2, "Language": "eng" },
+                { "Type": "Subtitle", "Index": 3, "Language": "ger", "IsForced": true },
+                { "Type": "Subtitle", "Index": 4, "Language": "eng", "IsForced": true },
+                { "Type": "Subtitle", "Index": 5, "Language": "eng", "IsForced": true, "IsExternal": true }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(source.forced_subtitle_track("eng"), Some(3));
+        assert_eq!(source.forced_subtitle_track("fre"), Some(2));
+    }
+
+    #[test]
+    fn library_stats_count_watched_runtime() {
+        let watched = |mut item: MediaItem| {
+            item.user_data = Some(UserData {
+                played: true,
+                ..Default::default()
+            });
+            item
+        };
+
+        let mut movie = episode(0, 0);
+        movie.type_ = "Movie".to_string();
+        movie.runtime_ticks = Some(300);
+
+        let mut first = watched(episode(1, 1));
+        first.runtime_ticks = Some(100);
+
+        let mut second = episode(1, 2);
+        second.runtime_ticks = Some(100);
+
+        let series = MediaItem {
+            type_: "Series".to_string(),
+            runtime_ticks: Some(1000),
+            ..Default::default()
+        };
+
+        let stats = LibraryStats::from_items(&[watched(movie), first, second, series]);
+
+        assert_eq!(stats.movies, 1);
+        assert_eq!(stats.series, 1);
+        assert_eq!(stats.episodes, 2);
+        assert_eq!(stats.watched_movies, 1);
+        assert_eq!(stats.watched_episodes, 1);
+        assert_eq!(stats.runtime_ticks, 500);
+        assert_eq!(stats.watched_percent(), 80);
+    }
+
+    #[test]
+    fn trailer_url_skips_empty_urls() {
+        let movie: MediaItem = serde_json::from_value(serde_json::json!({
+            "Id": "movie",
+            "Name": "The Matrix",
+            "Type": "Movie",
+            "RemoteTrailers": [
+                { "Url": "" },
+                { "Url": "https://www.youtube.com/watch?v=vKQi3bBA1y8", "Name": "Trailer" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            movie.trailer_url(),
+            Some("https://www.youtube.com/watch?v=vKQi3bBA1y8")
+        );
+        assert_eq!(MediaItem::default().trailer_url(), None);
+    }
+
+    #[test]
+    fn external_url_prefers_imdb() {
+        let mut movie = MediaItem {
+            type_: "Movie".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(movie.external_url(), None);
+
+        movie
+            .provider_ids
+            .insert("Tmdb".to_string(), "603".to_string());
+        assert_eq!(
+            movie.external_url().as_deref(),
+            Some("https://www.themoviedb.org/movie/603")
+        );
+
+        movie
+            .provider_ids
+            .insert("Imdb".to_string(), "tt0133093".to_string());
+        assert_eq!(
+            movie.external_url().as_deref(),
+            Some("https://www.imdb.com/title/tt0133093/")
+        );
+
+        let mut episode = episode(1, 1);
+        episode
+            .provider_ids
+            .insert("Tmdb".to_string(), "62085".to_string());
+        assert_eq!(episode.external_url(), None);
+    }
+
+    #[test]
+    fn remaining_ticks_subtracts_the_playback_position() {
+        let mut item = episode(1, 1);
+        item.runtime_ticks = Some(45 * 60 * 10_000_000);
+        item.user_data = Some(UserData {
+            playback_position_ticks: 15 * 60 * 10_000_000,
+            ..Default::default()
+        });
+
+        assert_eq!(format_ticks(item.remaining_ticks()), "30m");
+        assert_eq!(format_ticks(item.remaining_ticks() * 5), "2h 30m");
+    }
+
+    #[test]
+    fn server_version_parses_and_orders() {
+        let version: ServerVersion = "10.9.11".parse().unwrap();
+
+        assert_eq!(version, ServerVersion(10, 9, 11));
+        assert!(version > MIN_SERVER_VERSION);
+        assert!("10.7".parse::<ServerVersion>().unwrap() < MIN_SERVER_VERSION);
+        assert!("unstable".parse::<ServerVersion>().is_err());
+    }
+
+    #[test]
+    fn episodes_before_spans_earlier_seasons() {
+        let before = episodes_before(&series(), &episode(2, 2));
+
+        assert_eq!(
+            before.into_iter().map(|ep| ep.id).collect::<Vec<_>>(),
+            ["s1e1", "s1e2", "s1e3", "s1e4", "s1e5", "s2e1"]
+        );
+    }
+
+    #[test]
+    fn cache_round_trips_in_both_formats() {
+        let items = HashMap::from([("s1e1".to_string(), episode(1, 1))]);
+
+        for format in [CacheFormat::Json, CacheFormat::Messagepack] {
+            let cache = decode_cache(format, &encode_cache(format, &items).unwrap()).unwrap();
+
+            assert_eq!(cache.version, CACHE_VERSION);
+            assert_eq!(cache.items["s1e1"].index_number, Some(1));
+        }
+    }
+
+    #[test]
+    fn cache_without_version_is_outdated() {
+        let items = HashMap::from([("s1e1".to_string(), episode(1, 1))]);
+        let old = serde_json::to_vec(&items).unwrap();
+
+        assert_eq!(decode_cache(CacheFormat::Json, &old).unwrap().version, 0);
+    }
+
+    #[test]
+    fn media_items_are_the_same_by_id() {
+        let watched = MediaItem {
+            user_data: Some(UserData {
+                played: true,
+                ..Default::default()
+            }),
+            ..episode(1, 1)
+        };
+
+        assert_eq!(watched, episode(1, 1));
+        assert_ne!(episode(1, 1), episode(1, 2));
+        assert_eq!(
+            HashSet::from([watched, episode(1, 1), episode(1, 2)]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn backfill_series_context_keeps_what_the_server_sent() {
+        let cached = MediaItem {
+            series_id: Some("series".to_string()),
+            series_name: Some("Cached Name".to_string()),
+            ..episode(1, 2)
+        };
+        let cache = HashMap::from([(cached.id.clone(), cached)]);
+
+        let mut items = vec![MediaItem {
+            parent_index_number: None,
+            index_number: None,
+            series_name: Some("Server Name".to_string()),
+            ..episode(1, 2)
+        }];
+        backfill_series_context(&mut items, &cache);
+
+        assert_eq!(items[0].series_id.as_deref(), Some("series"));
+        assert_eq!(items[0].series_name.as_deref(), Some("Server Name"));
+        assert_eq!(items[0].parent_index_number, Some(1));
+        assert_eq!(items[0].index_number, Some(2));
+    }
+
+    #[test]
+    fn next_episode_mid_season() {
+        let next = next_episode(&series(), &episode(1, 2));
+
+        assert_eq!(next.map(|ep| ep.id), Some("s1e3".to_string()));
+    }
+
+    #[test]
+    fn next_episode_end_of_season() {
+        // S1E5 used to match S2E6 because it is index + 1
+        let next = next_episode(&series(), &episode(1, 5));
+
+        assert_eq!(next.map(|ep| ep.id), Some("s2e1".to_string()));
+    }
+
+    #[test]
+    fn next_episode_series_finale() {
+        let next = next_episode(&series(), &episode(2, 6));
+
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn next_episode_skips_missing_episodes() {
+        let episodes = vec![episode(1, 1), episode(1, 3), episode(3, 2)];
+
+        assert_eq!(
+            next_episode(&episodes, &episode(1, 1)).map(|ep| ep.id),
+            Some("s1e3".to_string())
+        );
+        assert_eq!(
+            next_episode(&episodes, &episode(1, 3)).map(|ep| ep.id),
+            Some("s3e2".to_string())
+        );
+    }
+
+    #[test]
+    fn last_lines_skips_blank_lines() {
+        let output = "Playing: video.mkv\n\n[ffmpeg] http: HTTP error 404\nFailed to open video.mkv.\n\nExiting... (Errors when loading file)\n";
+
+        assert_eq!(
+            last_lines(output, 2),
+            "Failed to open video.mkv.\nExiting... (Errors when loading file)"
+        );
+        assert_eq!(last_lines("", 3), "");
+    }
+}
@@ -1,11 +1,16 @@
 mod app;
 pub mod config;
-mod jellyfin;
+pub mod jellyfin;
+mod opensubtitles;
+mod state;
+mod theme;
+mod trakt;
+mod websocket;
 
 use anyhow::Result;
 use app::App;
 use crossterm::{
-    event::DisableMouseCapture,
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -14,7 +19,7 @@ use std::io;
 use std::path::Path;
 
 use crate::config::Config;
-use ratatui::{layout::Rect, DefaultTerminal, Frame};
+use ratatui::{layout::Rect, DefaultTerminal, Frame, TerminalOptions, Viewport};
 
 pub async fn run_app(
     mut opt_terminal: Option<&mut DefaultTerminal>,
@@ -22,32 +27,65 @@ pub async fn run_app(
     config: Config,
     render_outer: impl Fn(&mut Frame) -> Rect,
 ) -> Result<()> {
+    let alternate_screen = !config.no_altscreen;
+
     let jellyfin = Jellyfin::new(path, config, &mut opt_terminal, &render_outer).await?;
 
     let mut app = App::new(jellyfin)?;
 
-    let (terminal_new, mut terminal) = match opt_terminal {
+    let (terminal_new, terminal) = match opt_terminal {
         Some(terminal) => (false, terminal),
-        None => (true, &mut ratatui::init()),
+        None if alternate_screen => (true, &mut ratatui::init()),
+        // ? for debugging, output and panics stay in the scrollback instead of vanishing with the alternate screen
+        None => (
+            true,
+            &mut ratatui::init_with_options(TerminalOptions {
+                viewport: Viewport::Fullscreen,
+            }),
+        ),
     };
     if terminal_new {
         // init terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        if alternate_screen {
+            execute!(stdout, EnterAlternateScreen)?;
+        }
+        execute!(stdout, EnableMouseCapture)?;
+
+        set_panic_hook(alternate_screen);
     }
 
-    app.run(&mut terminal, &render_outer).await?;
+    // ? the terminal is restored before an error is passed on, so it can actually be read
+    let result = app.run(terminal, &render_outer).await;
 
     if terminal_new {
         // cleanup
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
+        restore_terminal(alternate_screen)?;
+    }
+
+    result
+}
+
+fn restore_terminal(alternate_screen: bool) -> io::Result<()> {
+    disable_raw_mode()?;
+
+    let mut stdout = io::stdout();
+    if alternate_screen {
+        execute!(stdout, LeaveAlternateScreen)?;
     }
+    execute!(stdout, DisableMouseCapture)?;
 
     Ok(())
 }
+
+// ? a crash would otherwise leave the terminal in raw mode with mouse reporting on, and the panic
+// ? message on the alternate screen where nobody gets to see it
+fn set_panic_hook(alternate_screen: bool) {
+    let hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal(alternate_screen);
+        hook(info);
+    }));
+}
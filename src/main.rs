@@ -11,14 +11,20 @@ use ratatui::{self, Frame};
 struct Args {
     #[arg(short, long)]
     base_path: Option<String>,
+    #[arg(
+        long,
+        help = "Draw on the normal screen, so errors stay visible in the scrollback after exiting"
+    )]
+    no_altscreen: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    let path = args.base_path.as_ref().map(|p| Path::new(p));
-    let config = Config::load(path)?;
+    let path = args.base_path.as_ref().map(Path::new);
+    let mut config = Config::load(path)?;
+    config.no_altscreen |= args.no_altscreen;
 
     run_app(Option::None, path, config, |frame: &mut Frame| frame.area()).await?;
 
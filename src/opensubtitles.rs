@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use anyhow::Result;
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::config::SubtitlesConfig;
+
+const API_URL: &str = "https://api.opensubtitles.com/api/v1";
+// ? opensubtitles rejects requests without a user agent naming the app
+const USER_AGENT: &str = concat!("jellytui v", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    data: Vec<Subtitle>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Subtitle {
+    attributes: SubtitleAttributes,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubtitleAttributes {
+    #[serde(default)]
+    files: Vec<SubtitleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubtitleFile {
+    file_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadResponse {
+    link: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenSubtitles {
+    client: Client,
+    config: SubtitlesConfig,
+}
+
+impl OpenSubtitles {
+    pub fn new(config: SubtitlesConfig) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().user_agent(USER_AGENT).build()?,
+            config,
+        })
+    }
+
+    // ? saves the most downloaded match to path
+    pub async fn download(&self, imdb_id: &str, path: &Path) -> Result<()> {
+        let search = self
+            .client
+            .get(format!("{}/subtitles", API_URL))
+            .header("Api-Key", &self.config.opensubtitles_api_key)
+            .query(&[
+                ("imdb_id", imdb_number(imdb_id)?.as_str()),
+                ("languages", self.config.languages()),
+                ("order_by", "download_count"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<SearchResponse>()
+            .await?;
+
+        let file_id = search
+            .data
+            .iter()
+            .flat_map(|subtitle| &subtitle.attributes.files)
+            .map(|file| file.file_id)
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No subtitles found for {}", imdb_id))?;
+
+        let download = self
+            .client
+            .post(format!("{}/download", API_URL))
+            .header("Api-Key", &self.config.opensubtitles_api_key)
+            .json(&serde_json::json!({ "file_id": file_id }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DownloadResponse>()
+            .await?;
+
+        let contents = self
+            .client
+            .get(download.link)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)?;
+
+        Ok(())
+    }
+}
+
+// ? the api wants imdb ids as plain numbers, without the tt prefix and leading zeros
+fn imdb_number(imdb_id: &str) -> Result<String> {
+    Ok(imdb_id.trim_start_matches("tt").parse::<u64>()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imdb_number_strips_prefix_and_zeros() {
+        assert_eq!(imdb_number("tt0133093").unwrap(), "133093");
+        assert!(imdb_number("nm-foo").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::app::Page;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct State {
+    #[serde(default)]
+    pub page: Option<Page>,
+    #[serde(default)]
+    pub selected_id: Option<String>,
+    #[serde(default)]
+    pub search_history: Vec<String>,
+    #[serde(default)]
+    pub latest_library: Option<String>,
+    // ? toggles changed in the app, which win over the defaults from config.toml
+    #[serde(default)]
+    pub include_episodes: Option<bool>,
+    #[serde(default)]
+    pub next_up_by_last_watched: Option<bool>,
+    #[serde(default)]
+    pub episodes_newest_first: Option<bool>,
+}
+
+impl State {
+    pub fn load(path: &Path) -> Self {
+        // ? a missing or unreadable state file just means a fresh start
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::BorderType;
+
+use crate::config::ThemeConfig;
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub selection_fg: Color,
+    pub selection_highlight: Color,
+    pub popup_border: Color,
+    pub title: Color,
+    pub normal_text: Color,
+    pub no_color: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            selection_fg: Color::Yellow,
+            selection_highlight: Color::Yellow,
+            popup_border: Color::Red,
+            title: Color::Reset,
+            normal_text: Color::Reset,
+            no_color: false,
+        }
+    }
+}
+
+impl Theme {
+    pub fn from_config(config: &ThemeConfig, no_color: bool) -> Result<Self> {
+        // selection and popups fall back to modifiers and border types in this mode
+        if no_color {
+            return Ok(Self {
+                selection_fg: Color::Reset,
+                selection_highlight: Color::Reset,
+                popup_border: Color::Reset,
+                title: Color::Reset,
+                normal_text: Color::Reset,
+                no_color,
+            });
+        }
+
+        let default = Self::default();
+
+        Ok(Self {
+            selection_fg: parse_color(&config.selection_fg, default.selection_fg)?,
+            selection_highlight: parse_color(
+                &config.selection_highlight,
+                default.selection_highlight,
+            )?,
+            popup_border: parse_color(&config.popup_border, default.popup_border)?,
+            title: parse_color(&config.title, default.title)?,
+            normal_text: parse_color(&config.normal_text, default.normal_text)?,
+            no_color,
+        })
+    }
+
+    pub fn text(&self) -> Style {
+        Style::default().fg(self.normal_text)
+    }
+
+    pub fn selection(&self) -> Style {
+        let style = Style::default()
+            .fg(self.selection_fg)
+            .add_modifier(Modifier::BOLD);
+
+        if self.no_color {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
+
+    pub fn popup_border_type(&self) -> BorderType {
+        if self.no_color {
+            BorderType::Double
+        } else {
+            BorderType::Plain
+        }
+    }
+}
+
+// ? accepts color names ("yellow", "lightblue"), hex ("#ffcc00") and 256 color indices ("208")
+fn parse_color(value: &Option<String>, default: Color) -> Result<Color> {
+    match value {
+        Some(value) => {
+            Color::from_str(value).map_err(|_| anyhow::anyhow!("Invalid theme color: {}", value))
+        }
+        None => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_config_defaults_and_overrides() {
+        let theme = Theme::from_config(
+            &ThemeConfig {
+                selection_fg: Some("#ff0000".to_string()),
+                popup_border: Some("blue".to_string()),
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(theme.selection_fg, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.selection_highlight, Color::Yellow);
+        assert_eq!(theme.popup_border, Color::Blue);
+    }
+
+    #[test]
+    fn from_config_rejects_invalid_colors() {
+        assert!(Theme::from_config(
+            &ThemeConfig {
+                title: Some("not a color".to_string()),
+                ..Default::default()
+            },
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn from_config_no_color_ignores_colors() {
+        let theme = Theme::from_config(
+            &ThemeConfig {
+                selection_fg: Some("yellow".to_string()),
+                ..Default::default()
+            },
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(theme.selection_fg, Color::Reset);
+        assert!(theme.selection().add_modifier.contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use reqwest::Client;
+
+use crate::config::TraktConfig;
+use crate::jellyfin::MediaItem;
+
+const API_URL: &str = "https://api.trakt.tv";
+
+#[derive(Debug, Clone)]
+pub struct Trakt {
+    client: Client,
+    config: TraktConfig,
+}
+
+impl Trakt {
+    pub fn new(config: TraktConfig) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().build()?,
+            config,
+        })
+    }
+
+    // ? action is one of trakt's scrobble endpoints: "start", "pause" or "stop"
+    pub async fn scrobble(
+        &self,
+        action: &str,
+        item: &MediaItem,
+        series: Option<&MediaItem>,
+        progress: f64,
+    ) -> Result<()> {
+        let mut body = match (item.type_.as_str(), series) {
+            ("Episode", Some(series)) if !series.provider_ids.is_empty() => serde_json::json!({
+                "show": { "ids": Self::ids(&series.provider_ids) },
+                "episode": {
+                    "season": item.parent_index_number,
+                    "number": item.index_number
+                }
+            }),
+            ("Episode", _) => serde_json::json!({
+                "episode": { "ids": Self::ids(&item.provider_ids) }
+            }),
+            _ => serde_json::json!({
+                "movie": { "ids": Self::ids(&item.provider_ids) }
+            }),
+        };
+
+        body["progress"] = serde_json::json!(progress.clamp(0.0, 100.0));
+
+        self.client
+            .post(format!("{}/scrobble/{}", API_URL, action))
+            .header("trakt-api-version", "2")
+            .header("trakt-api-key", &self.config.client_id)
+            .bearer_auth(&self.config.access_token)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    fn ids(provider_ids: &HashMap<String, String>) -> serde_json::Value {
+        provider_ids
+            .iter()
+            .filter_map(|(provider, id)| {
+                let key = provider.to_lowercase();
+
+                // ? trakt expects numeric ids for everything but imdb
+                match key.as_str() {
+                    "imdb" => Some((key, serde_json::json!(id))),
+                    "tmdb" | "tvdb" => id
+                        .parse::<i64>()
+                        .ok()
+                        .map(|id| (key, serde_json::json!(id))),
+                    _ => None,
+                }
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug)]
+pub enum ServerEvent {
+    LibraryChanged {
+        changed: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+// ? remote control commands are forwarded straight to the mpv instance that is currently playing,
+// ? everything else is handed to the main loop through the returned channel
+pub fn spawn(
+    endpoint: &str,
+    access_token: &str,
+    device_id: &str,
+    mpv_socket: Arc<Mutex<Option<String>>>,
+) -> UnboundedReceiver<ServerEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    let url = format!(
+        "{}?api_key={}&deviceId={}",
+        endpoint
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1),
+        access_token,
+        device_id
+    );
+
+    tokio::spawn(async move {
+        // reconnect until the app exits, the server may restart while we're running
+        loop {
+            if run(&url, &sender, &mpv_socket).await.is_err() && sender.is_closed() {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        }
+    });
+
+    receiver
+}
+
+async fn run(
+    url: &str,
+    sender: &UnboundedSender<ServerEvent>,
+    mpv_socket: &Arc<Mutex<Option<String>>>,
+) -> anyhow::Result<()> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+
+    // the server tells us how often it expects a keep alive once connected
+    let mut keep_alive: Option<tokio::time::Interval> = None;
+
+    loop {
+        let message = tokio::select! {
+            message = socket.next() => message,
+            _ = async { keep_alive.as_mut().unwrap().tick().await }, if keep_alive.is_some() => {
+                socket
+                    .send(Message::Text(r#"{"MessageType":"KeepAlive"}"#.to_string()))
+                    .await?;
+                continue;
+            }
+        };
+
+        let Some(message) = message else {
+            return Ok(());
+        };
+
+        let Message::Text(text) = message? else {
+            continue;
+        };
+
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+
+        let data = &message["Data"];
+
+        match message["MessageType"].as_str().unwrap_or_default() {
+            "ForceKeepAlive" => {
+                let timeout = data.as_u64().unwrap_or(60).max(2);
+                keep_alive = Some(tokio::time::interval(Duration::from_secs(timeout / 2)));
+            }
+            "LibraryChanged" => sender.send(ServerEvent::LibraryChanged {
+                changed: ids(&data["ItemsAdded"])
+                    .chain(ids(&data["ItemsUpdated"]))
+                    .collect(),
+                removed: ids(&data["ItemsRemoved"]).collect(),
+            })?,
+            "Playstate" => {
+                let command = match data["Command"].as_str().unwrap_or_default() {
+                    "PlayPause" => serde_json::json!(["cycle", "pause"]),
+                    "Pause" => serde_json::json!(["set_property", "pause", true]),
+                    "Unpause" => serde_json::json!(["set_property", "pause", false]),
+                    "Stop" => serde_json::json!(["quit"]),
+                    "NextTrack" => serde_json::json!(["playlist-next"]),
+                    "PreviousTrack" => serde_json::json!(["playlist-prev"]),
+                    "Seek" => serde_json::json!([
+                        "seek",
+                        data["SeekPositionTicks"].as_i64().unwrap_or(0) as f64 / 10_000_000.0,
+                        "absolute"
+                    ]),
+                    _ => continue,
+                };
+
+                send_to_mpv(mpv_socket, command).await;
+            }
+            "GeneralCommand" => {
+                let command = match data["Name"].as_str().unwrap_or_default() {
+                    "ToggleMute" => serde_json::json!(["cycle", "mute"]),
+                    "SetVolume" => serde_json::json!([
+                        "set_property",
+                        "volume",
+                        data["Arguments"]["Volume"]
+                            .as_str()
+                            .and_then(|volume| volume.parse::<i64>().ok())
+                            .unwrap_or(100)
+                    ]),
+                    _ => continue,
+                };
+
+                send_to_mpv(mpv_socket, command).await;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn ids(value: &serde_json::Value) -> impl Iterator<Item = String> + '_ {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|id| id.as_str().map(str::to_string))
+}
+
+async fn send_to_mpv(mpv_socket: &Arc<Mutex<Option<String>>>, command: serde_json::Value) {
+    let Some(socket_path) = mpv_socket.lock().ok().and_then(|path| path.clone()) else {
+        return;
+    };
+
+    let Ok(mut socket) = UnixStream::connect(socket_path).await else {
+        return;
+    };
+
+    // ? the player may have quit in the meantime, the command is simply dropped then
+    let _ = socket
+        .write_all(format!("{}\n", serde_json::json!({ "command": command })).as_bytes())
+        .await;
+}